@@ -1,52 +1,423 @@
-use anyhow::bail;
 use borsh::BorshDeserialize;
-use solana_client_wasm::WasmClient;
-use solana_extra_wasm::account_decoder::parse_token::UiTokenAccount;
-use solana_sdk::{borsh::try_from_slice_unchecked, pubkey::Pubkey};
+use futures::{stream, StreamExt, TryStreamExt};
+use solana_extra_wasm::{
+    account_decoder::parse_token::UiTokenAccount,
+    program::spl_token_2022::extension::{BaseState, StateWithExtensions},
+};
+use solana_sdk::{
+    account::Account,
+    borsh::try_from_slice_unchecked,
+    program_pack::{IsInitialized, Pack},
+    pubkey::Pubkey,
+};
+
+use crate::core::{
+    error::{Web3WasmError, Web3WasmResult},
+    rpc::{RpcClient, RpcFuture},
+    token_2022::parse_tlv,
+};
+
+/// `getMultipleAccounts` rejects requests with more keys than this.
+pub const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+
+/// Chunk requests in flight at once.
+pub const MAX_CONCURRENT_CHUNKS: usize = 4;
+
+/// Byte range of account data to download, sent as the RPC `dataSlice`.
+///
+/// The node returns fewer bytes when the range goes past the end of the account.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DataSlice {
+    pub offset: usize,
+    pub length: usize,
+}
+
+impl DataSlice {
+    pub fn new(offset: usize, length: usize) -> Self {
+        Self { offset, length }
+    }
+}
+
+/// What a batch fetcher got for one requested key.
+///
+/// Batch fetchers return one entry per key, in the order of the keys.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccountResult<T> {
+    Found(T),
+    /// No account at that address.
+    Missing,
+    /// The account exists but its data could not be decoded, with the reason.
+    DecodeError(String),
+}
+
+impl<T> AccountResult<T> {
+    pub fn is_found(&self) -> bool {
+        matches!(self, AccountResult::Found(_))
+    }
+
+    pub fn found(self) -> Option<T> {
+        match self {
+            AccountResult::Found(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_found(&self) -> Option<&T> {
+        match self {
+            AccountResult::Found(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> AccountResult<U> {
+        match self {
+            AccountResult::Found(value) => AccountResult::Found(f(value)),
+            AccountResult::Missing => AccountResult::Missing,
+            AccountResult::DecodeError(reason) => AccountResult::DecodeError(reason),
+        }
+    }
+
+    /// `Missing` becomes `NotFound` and `DecodeError` becomes `Decode`, both naming `pubkey`.
+    pub fn into_result(self, pubkey: &Pubkey) -> Web3WasmResult<T> {
+        match self {
+            AccountResult::Found(value) => Ok(value),
+            AccountResult::Missing => Err(Web3WasmError::NotFound(format!(
+                "AccountNotFound: pubkey={pubkey}"
+            ))),
+            AccountResult::DecodeError(reason) => Err(Web3WasmError::Decode(format!(
+                "Unable to decode {pubkey}: {reason}"
+            ))),
+        }
+    }
+}
+
+/// A decoder's error becomes `DecodeError` with its message.
+impl<T> From<Web3WasmResult<T>> for AccountResult<T> {
+    fn from(result: Web3WasmResult<T>) -> Self {
+        match result {
+            Ok(value) => AccountResult::Found(value),
+            Err(err) => AccountResult::DecodeError(err.to_string()),
+        }
+    }
+}
+
+impl<T> From<Option<T>> for AccountResult<T> {
+    fn from(value: Option<T>) -> Self {
+        match value {
+            Some(value) => AccountResult::Found(value),
+            None => AccountResult::Missing,
+        }
+    }
+}
+
+/// Splits `pubkeys` into RPC sized chunks, fetches them with bounded
+/// concurrency and stitches the results back together in input order.
+pub(crate) async fn fetch_chunked<'a, T, F>(
+    pubkeys: &'a [Pubkey],
+    fetch: F,
+) -> Web3WasmResult<Vec<T>>
+where
+    F: Fn(&'a [Pubkey]) -> RpcFuture<'a, Vec<T>>,
+{
+    let chunks = stream::iter(pubkeys.chunks(MAX_MULTIPLE_ACCOUNTS))
+        .map(|chunk| {
+            let future = fetch(chunk);
+            async move {
+                let results = future.await?;
+                if results.len() != chunk.len() {
+                    return Err(Web3WasmError::Decode(format!(
+                        "Expected {} accounts, got {}",
+                        chunk.len(),
+                        results.len()
+                    )));
+                }
+                Ok(results)
+            }
+        })
+        .buffered(MAX_CONCURRENT_CHUNKS)
+        .try_collect::<Vec<_>>()
+        .await?;
+
+    Ok(chunks.into_iter().flatten().collect())
+}
+
+pub async fn get_multiple_accounts_chunked(
+    client: &impl RpcClient,
+    pubkeys: &[Pubkey],
+) -> Web3WasmResult<Vec<Option<Account>>> {
+    fetch_chunked(pubkeys, |chunk| client.get_multiple_accounts(chunk)).await
+}
+
+pub async fn get_multiple_token_accounts_chunked(
+    client: &impl RpcClient,
+    pubkeys: &[Pubkey],
+) -> Web3WasmResult<Vec<Option<UiTokenAccount>>> {
+    fetch_chunked(pubkeys, |chunk| client.get_multiple_token_accounts(chunk)).await
+}
+
+/// Downloads only `slice` of each account, `None` for missing accounts.
+pub async fn get_multiple_accounts_data_slice_chunked(
+    client: &impl RpcClient,
+    pubkeys: &[Pubkey],
+    slice: DataSlice,
+) -> Web3WasmResult<Vec<Option<Vec<u8>>>> {
+    let accounts = fetch_chunked(pubkeys, |chunk| {
+        client.get_multiple_accounts_data_slice(chunk, slice)
+    })
+    .await?;
+    Ok(accounts
+        .into_iter()
+        .map(|account| account.map(|account| account.data))
+        .collect())
+}
+
+pub async fn get_account_data_slice(
+    client: &impl RpcClient,
+    pubkey: &Pubkey,
+    slice: DataSlice,
+) -> Web3WasmResult<Vec<u8>> {
+    let mut accounts = client
+        .get_multiple_accounts_data_slice(&[*pubkey], slice)
+        .await?;
+    AccountResult::from(accounts.pop().flatten())
+        .map(|account| account.data)
+        .into_result(pubkey)
+}
+
+/// Fixed-size value stored little-endian in account data, as Borsh and `Pack` lay them out.
+pub trait AccountField: Sized {
+    const LEN: usize;
+
+    /// `bytes` is exactly `LEN` long.
+    fn from_bytes(bytes: &[u8]) -> Self;
+}
+
+macro_rules! impl_account_field {
+    ($($ty:ty),*) => {
+        $(
+            impl AccountField for $ty {
+                const LEN: usize = std::mem::size_of::<$ty>();
+
+                fn from_bytes(bytes: &[u8]) -> Self {
+                    <$ty>::from_le_bytes(bytes.try_into().unwrap())
+                }
+            }
+        )*
+    };
+}
+
+impl_account_field!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+impl AccountField for bool {
+    const LEN: usize = 1;
+
+    fn from_bytes(bytes: &[u8]) -> Self {
+        bytes[0] != 0
+    }
+}
+
+impl AccountField for Pubkey {
+    const LEN: usize = 32;
+
+    fn from_bytes(bytes: &[u8]) -> Self {
+        Pubkey::new_from_array(bytes.try_into().unwrap())
+    }
+}
+
+/// Reads a `T` at `offset` of `data`.
+pub fn read_field<T: AccountField>(data: &[u8], offset: usize) -> Web3WasmResult<T> {
+    let bytes = offset
+        .checked_add(T::LEN)
+        .and_then(|end| data.get(offset..end));
+    match bytes {
+        Some(bytes) => Ok(T::from_bytes(bytes)),
+        None => Err(Web3WasmError::Decode(format!(
+            "Expected {} bytes at offset {}, account data is {} bytes",
+            T::LEN,
+            offset,
+            data.len()
+        ))),
+    }
+}
+
+/// Downloads and reads only the `T` at `offset` of the account.
+pub async fn get_account_field<T: AccountField>(
+    client: &impl RpcClient,
+    pubkey: &Pubkey,
+    offset: usize,
+) -> Web3WasmResult<T> {
+    let data = get_account_data_slice(client, pubkey, DataSlice::new(offset, T::LEN)).await?;
+    read_field(&data, 0)
+}
+
+/// Batch version of [`get_account_field`], one entry per key.
+pub async fn get_multiple_accounts_field<T: AccountField>(
+    client: &impl RpcClient,
+    pubkeys: &[Pubkey],
+    offset: usize,
+) -> Web3WasmResult<Vec<AccountResult<T>>> {
+    let slices =
+        get_multiple_accounts_data_slice_chunked(client, pubkeys, DataSlice::new(offset, T::LEN))
+            .await?;
+    Ok(slices
+        .into_iter()
+        .map(|data| match data {
+            Some(data) => read_field(&data, 0).into(),
+            None => AccountResult::Missing,
+        })
+        .collect())
+}
 
 pub async fn get_and_deserialize_account_data<T: BorshDeserialize>(
-    client: &WasmClient,
+    client: &impl RpcClient,
     pubkey: &Pubkey,
-) -> anyhow::Result<T> {
+) -> Web3WasmResult<T> {
     let data = client.get_account_data(pubkey).await?;
     Ok(try_from_slice_unchecked::<T>(&data)?)
 }
 
+/// Borsh-decodes account data, ignoring trailing bytes.
+pub(crate) fn decode_borsh<T: BorshDeserialize>(data: &[u8]) -> AccountResult<T> {
+    try_from_slice_unchecked::<T>(data)
+        .map_err(Web3WasmError::decode)
+        .into()
+}
+
 pub async fn get_and_deserialize_multiple_accounts_data<T: BorshDeserialize>(
-    client: &WasmClient,
+    client: &impl RpcClient,
     pubkeys: &[Pubkey],
-) -> anyhow::Result<Vec<Option<T>>> {
-    let maybe_accounts = client.get_multiple_accounts(pubkeys).await?;
+) -> Web3WasmResult<Vec<AccountResult<T>>> {
+    let maybe_accounts = get_multiple_accounts_chunked(client, pubkeys).await?;
     let accounts = maybe_accounts
         .into_iter()
         .map(|account| match account {
-            Some(account) => match try_from_slice_unchecked::<T>(&account.data) {
-                Ok(a) => Some(a),
-                Err(_) => None,
-            },
-            None => None,
+            Some(account) => decode_borsh(&account.data),
+            None => AccountResult::Missing,
         })
         .collect::<Vec<_>>();
     Ok(accounts)
 }
 
+/// Token accounts of `pubkeys`, `DecodeError` for accounts that exist but are not one.
+///
+/// `jsonParsed` answers `None` for both, so those keys are looked up again with an
+/// empty data slice to tell them apart.
 pub async fn get_multiple_token_amount(
-    client: &WasmClient,
+    client: &impl RpcClient,
     pubkeys: &[Pubkey],
-) -> anyhow::Result<Vec<UiTokenAccount>> {
-    let maybe_accounts = client.get_multiple_token_accounts(pubkeys).await?;
+) -> Web3WasmResult<Vec<AccountResult<UiTokenAccount>>> {
+    let maybe_accounts = get_multiple_token_accounts_chunked(client, pubkeys).await?;
 
-    let accounts = maybe_accounts
+    let unparsed_keys = pubkeys
+        .iter()
+        .zip(&maybe_accounts)
+        .filter(|(_, account)| account.is_none())
+        .map(|(pubkey, _)| *pubkey)
+        .collect::<Vec<_>>();
+    let mut unparsed = if unparsed_keys.is_empty() {
+        vec![]
+    } else {
+        get_multiple_accounts_data_slice_chunked(client, &unparsed_keys, DataSlice::new(0, 0))
+            .await?
+    }
+    .into_iter();
+
+    Ok(maybe_accounts
         .into_iter()
-        .enumerate()
-        .flat_map(|(i, account)| {
-            Ok(match account {
-                Some(account) => account,
-                None => bail!("Not found:{}", pubkeys[i]),
-            })
+        .map(|account| match account {
+            Some(account) => AccountResult::Found(account),
+            None => match unparsed.next().flatten() {
+                Some(_) => AccountResult::DecodeError("not a token account".to_owned()),
+                None => AccountResult::Missing,
+            },
         })
-        .collect::<Vec<_>>();
-    Ok(accounts)
+        .collect())
+}
+
+/// Unpacks account data of a `Pack` type, which must be exactly `T::LEN` bytes and initialized.
+pub(crate) fn decode_pack<T: Pack + IsInitialized>(data: &[u8]) -> AccountResult<T> {
+    T::unpack(data).map_err(Web3WasmError::decode).into()
+}
+
+pub async fn get_and_unpack_account_data<T: Pack + IsInitialized>(
+    client: &impl RpcClient,
+    pubkey: &Pubkey,
+) -> Web3WasmResult<T> {
+    let data = client.get_account_data(pubkey).await?;
+    decode_pack(&data).into_result(pubkey)
+}
+
+/// `Pack` counterpart of [`get_and_deserialize_multiple_accounts_data`].
+pub async fn get_and_unpack_multiple_accounts_data<T: Pack + IsInitialized>(
+    client: &impl RpcClient,
+    pubkeys: &[Pubkey],
+) -> Web3WasmResult<Vec<AccountResult<T>>> {
+    let maybe_accounts = get_multiple_accounts_chunked(client, pubkeys).await?;
+    Ok(maybe_accounts
+        .into_iter()
+        .map(|account| match account {
+            Some(account) => decode_pack(&account.data),
+            None => AccountResult::Missing,
+        })
+        .collect())
+}
+
+/// Owned Token-2022 state: the base `Mint` or `Account` and the extensions that follow it.
+///
+/// Plain SPL Token accounts decode too, with no extensions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenStateWithExtensions<S> {
+    pub base: S,
+    /// Raw `ExtensionType` numbers, also the ones newer than the bundled `spl-token-2022`.
+    pub extension_types: Vec<u16>,
+    /// Raw account data, to read extension values with [`Self::state`].
+    pub data: Vec<u8>,
+}
+
+impl<S: BaseState> TokenStateWithExtensions<S> {
+    pub fn unpack(data: Vec<u8>) -> Web3WasmResult<Self> {
+        let state = StateWithExtensions::<S>::unpack(&data)?;
+        let extension_types = parse_tlv(&data)?
+            .into_iter()
+            .map(|entry| entry.extension_type)
+            .collect();
+        Ok(Self {
+            base: state.base,
+            extension_types,
+            data,
+        })
+    }
+
+    /// Borrowed view of the data, e.g. for `get_extension::<TransferFeeConfig>()`.
+    ///
+    /// Its lookups fail past extensions the bundled `spl-token-2022` does not know, see
+    /// [`crate::core::token_2022`] for those.
+    pub fn state(&self) -> Web3WasmResult<StateWithExtensions<'_, S>> {
+        Ok(StateWithExtensions::<S>::unpack(&self.data)?)
+    }
+}
+
+pub async fn get_and_unpack_token_state<S: BaseState>(
+    client: &impl RpcClient,
+    pubkey: &Pubkey,
+) -> Web3WasmResult<TokenStateWithExtensions<S>> {
+    let data = client.get_account_data(pubkey).await?;
+    TokenStateWithExtensions::unpack(data)
+}
+
+/// Batch version of [`get_and_unpack_token_state`], one entry per key.
+pub async fn get_and_unpack_multiple_token_states<S: BaseState>(
+    client: &impl RpcClient,
+    pubkeys: &[Pubkey],
+) -> Web3WasmResult<Vec<AccountResult<TokenStateWithExtensions<S>>>> {
+    let maybe_accounts = get_multiple_accounts_chunked(client, pubkeys).await?;
+    Ok(maybe_accounts
+        .into_iter()
+        .map(|account| match account {
+            Some(account) => TokenStateWithExtensions::unpack(account.data).into(),
+            None => AccountResult::Missing,
+        })
+        .collect())
 }
 
 #[cfg(test)]
@@ -54,10 +425,103 @@ pub async fn get_multiple_token_amount(
 mod test {
     use std::str::FromStr;
 
-    use solana_client_wasm::WasmClient;
-    use solana_sdk::pubkey::Pubkey;
+    use serde_json::{json, Value};
+    use solana_extra_wasm::program::{
+        spl_token::{self, state::Mint},
+        spl_token_2022::state::Mint as Mint2022,
+    };
+    use solana_sdk::{program_option::COption, program_pack::Pack, pubkey::Pubkey, system_program};
+
+    use crate::{
+        core::{
+            account::{
+                get_account_field, get_and_deserialize_multiple_accounts_data,
+                get_and_unpack_multiple_accounts_data, get_and_unpack_multiple_token_states,
+                get_multiple_accounts_field, get_multiple_token_amount, read_field, AccountResult,
+                MAX_MULTIPLE_ACCOUNTS,
+            },
+            client::{ClientConfig, EndPoint},
+            json_rpc::JsonRpcClient,
+            token_2022::{
+                test::{metadata_pointer, mint_with_extensions, token_metadata},
+                METADATA_POINTER_EXTENSION, MINT_CLOSE_AUTHORITY_EXTENSION,
+                TOKEN_METADATA_EXTENSION,
+            },
+        },
+        tests::{
+            fixtures::fixture_client,
+            rpc_server::{account_json, StandInResponse, StandInRpcServer},
+        },
+    };
+
+    /// Serves every requested key as an account whose data is the key itself.
+    fn echo_accounts(method: &str, params: &Value) -> StandInResponse {
+        assert_eq!(method, "getMultipleAccounts");
+        let keys = params[0].as_array().unwrap();
+        if keys.len() > MAX_MULTIPLE_ACCOUNTS {
+            return StandInResponse::rpc_error(-32602, "Too many inputs provided");
+        }
+
+        StandInResponse::accounts(params, |key| {
+            Some(account_json(&system_program::id(), &key.to_bytes()))
+        })
+    }
+
+    #[tokio::test]
+    async fn test_get_and_deserialize_multiple_accounts_data_chunked() {
+        let server = StandInRpcServer::start(echo_accounts).await;
+        let client = JsonRpcClient::new(ClientConfig::new(EndPoint::CustomUrl(server.url())));
+        let pubkeys = (0..250).map(|_| Pubkey::new_unique()).collect::<Vec<_>>();
+
+        let results = get_and_deserialize_multiple_accounts_data::<Pubkey>(&client, &pubkeys)
+            .await
+            .unwrap();
 
-    use crate::core::{account::get_multiple_token_amount, client::EndPoint};
+        assert_eq!(server.request_count("getMultipleAccounts"), 3);
+        assert_eq!(
+            results
+                .into_iter()
+                .filter_map(AccountResult::found)
+                .collect::<Vec<_>>(),
+            pubkeys
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_and_deserialize_multiple_accounts_data_partial() {
+        let server = StandInRpcServer::start(|_, _| {
+            let owner = system_program::id();
+            let found = Pubkey::new_from_array([7; 32]);
+            StandInResponse::value(json!([
+                Value::Null,
+                account_json(&owner, &found.to_bytes()),
+                account_json(&owner, &[1, 2])
+            ]))
+        })
+        .await;
+        let client = JsonRpcClient::new(ClientConfig::new(EndPoint::CustomUrl(server.url())));
+        let pubkeys = (0..3).map(|_| Pubkey::new_unique()).collect::<Vec<_>>();
+
+        let results = get_and_deserialize_multiple_accounts_data::<Pubkey>(&client, &pubkeys)
+            .await
+            .unwrap();
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0], AccountResult::Missing);
+        assert_eq!(
+            results[1],
+            AccountResult::Found(Pubkey::new_from_array([7; 32]))
+        );
+        assert!(matches!(results[2], AccountResult::DecodeError(_)));
+        assert_eq!(
+            results[0]
+                .clone()
+                .into_result(&pubkeys[0])
+                .unwrap_err()
+                .code(),
+            "NOT_FOUND"
+        );
+    }
 
     // TODO: find some BorshDeserialize pubkey
     // #[tokio::test]
@@ -75,7 +539,7 @@ mod test {
 
     #[tokio::test]
     async fn test_get_multiple_token_amount() {
-        let client = WasmClient::new(EndPoint::Mainnet.to_string().as_ref());
+        let client = fixture_client("token_account");
         let results = get_multiple_token_amount(
             &client,
             &[Pubkey::from_str("99WfGSsxb8zRsr3GwsZHSCT6bi1FHVR3RpQhV51cyu6B").unwrap()],
@@ -84,8 +548,233 @@ mod test {
         .unwrap();
 
         assert_eq!(
-            results[0].owner,
+            results[0].as_found().unwrap().owner,
             "3GGFZQ1krmhhviptR4Az1xaF62XFkAKDKDSNShQZVBeE"
-        )
+        );
+        assert_eq!(
+            results[0].as_found().unwrap().token_amount.amount,
+            "1500000"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_multiple_token_amount_not_a_token_account() {
+        let fixture: Value =
+            serde_json::from_str(include_str!("../../tests/fixtures/token_account.json")).unwrap();
+        let token_account = fixture[0]["response"]["result"]["value"][0].clone();
+        let token = Pubkey::new_unique();
+        let wallet = Pubkey::new_unique();
+        let missing = Pubkey::new_unique();
+
+        let server = StandInRpcServer::start(move |_, params| {
+            StandInResponse::accounts(params, |key| match *key {
+                key if key == token => Some(token_account.clone()),
+                key if key == wallet => Some(account_json(&system_program::id(), &[])),
+                _ => None,
+            })
+        })
+        .await;
+        let client = JsonRpcClient::new(ClientConfig::new(EndPoint::CustomUrl(server.url())));
+
+        let results = get_multiple_token_amount(&client, &[token, wallet, missing])
+            .await
+            .unwrap();
+
+        assert_eq!(
+            results[0].as_found().unwrap().token_amount.amount,
+            "1500000"
+        );
+        assert_eq!(
+            results[1],
+            AccountResult::DecodeError("not a token account".to_owned())
+        );
+        assert_eq!(results[2], AccountResult::Missing);
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[1].body["params"][0].as_array().unwrap().len(), 2);
+    }
+
+    fn packed_mint(decimals: u8) -> Vec<u8> {
+        let mint = Mint {
+            mint_authority: COption::Some(Pubkey::new_from_array([1; 32])),
+            supply: 1_000,
+            decimals,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        };
+        let mut data = vec![0u8; Mint::LEN];
+        mint.pack_into_slice(&mut data);
+        data
+    }
+
+    /// Token-2022 mint with a `MintCloseAuthority` extension.
+    fn packed_mint_2022(decimals: u8) -> Vec<u8> {
+        let mut data = packed_mint(decimals);
+        // Padded up to the size of a token account, then the account type byte.
+        data.resize(165, 0);
+        data.push(1);
+        data.extend(3u16.to_le_bytes());
+        data.extend(32u16.to_le_bytes());
+        data.extend([2; 32]);
+        data
+    }
+
+    fn serve_accounts(accounts: Vec<Option<Vec<u8>>>) -> impl Fn(&str, &Value) -> StandInResponse {
+        move |_, _| {
+            let value = accounts
+                .iter()
+                .map(|data| match data {
+                    Some(data) => account_json(&spl_token::id(), data),
+                    None => Value::Null,
+                })
+                .collect::<Vec<_>>();
+            StandInResponse::value(json!(value))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_and_unpack_multiple_accounts_data() {
+        let server = StandInRpcServer::start(serve_accounts(vec![
+            Some(packed_mint(6)),
+            None,
+            Some(vec![0; Mint::LEN]),
+            Some(packed_mint_2022(9)),
+        ]))
+        .await;
+        let client = JsonRpcClient::new(ClientConfig::new(EndPoint::CustomUrl(server.url())));
+        let pubkeys = (0..4).map(|_| Pubkey::new_unique()).collect::<Vec<_>>();
+
+        let results = get_and_unpack_multiple_accounts_data::<Mint>(&client, &pubkeys)
+            .await
+            .unwrap();
+
+        assert_eq!(results[0].as_found().unwrap().decimals, 6);
+        assert_eq!(results[1], AccountResult::Missing);
+        // Uninitialized.
+        assert!(matches!(results[2], AccountResult::DecodeError(_)));
+        // `Pack` needs the exact length, extensions do not fit.
+        assert!(matches!(results[3], AccountResult::DecodeError(_)));
+    }
+
+    #[tokio::test]
+    async fn test_get_and_unpack_multiple_token_states() {
+        let metadata_mint = Pubkey::new_unique();
+        let server = StandInRpcServer::start(serve_accounts(vec![
+            Some(packed_mint(6)),
+            Some(packed_mint_2022(9)),
+            None,
+            Some(vec![1, 2, 3]),
+            Some(mint_with_extensions(&[
+                metadata_pointer(&metadata_mint),
+                token_metadata(&metadata_mint, "Paxos Gold", "PAXG"),
+            ])),
+        ]))
+        .await;
+        let client = JsonRpcClient::new(ClientConfig::new(EndPoint::CustomUrl(server.url())));
+        let pubkeys = (0..5).map(|_| Pubkey::new_unique()).collect::<Vec<_>>();
+
+        let results = get_and_unpack_multiple_token_states::<Mint2022>(&client, &pubkeys)
+            .await
+            .unwrap();
+
+        let legacy = results[0].as_found().unwrap();
+        assert_eq!(legacy.base.decimals, 6);
+        assert!(legacy.extension_types.is_empty());
+
+        let mint = results[1].as_found().unwrap();
+        assert_eq!(mint.base.decimals, 9);
+        assert_eq!(mint.base.supply, 1_000);
+        assert_eq!(mint.extension_types, vec![MINT_CLOSE_AUTHORITY_EXTENSION]);
+
+        assert_eq!(results[2], AccountResult::Missing);
+        assert!(matches!(results[3], AccountResult::DecodeError(_)));
+
+        // Extensions newer than the bundled spl-token-2022 do not fail the mint.
+        let with_metadata = results[4].as_found().unwrap();
+        assert_eq!(with_metadata.base.decimals, 6);
+        assert_eq!(
+            with_metadata.extension_types,
+            vec![METADATA_POINTER_EXTENSION, TOKEN_METADATA_EXTENSION]
+        );
+    }
+
+    #[test]
+    fn test_read_field() {
+        let mut data = vec![0u8; 8];
+        data.extend(Pubkey::new_from_array([4; 32]).to_bytes());
+        data.extend(42u64.to_le_bytes());
+        data.push(1);
+
+        assert_eq!(
+            read_field::<Pubkey>(&data, 8).unwrap(),
+            Pubkey::new_from_array([4; 32])
+        );
+        assert_eq!(read_field::<u64>(&data, 40).unwrap(), 42);
+        assert!(read_field::<bool>(&data, 48).unwrap());
+        assert_eq!(
+            read_field::<u64>(&data, 45).unwrap_err().code(),
+            "DECODE_ERROR"
+        );
+        assert_eq!(
+            read_field::<Pubkey>(&data, usize::MAX - 8)
+                .unwrap_err()
+                .code(),
+            "DECODE_ERROR"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_multiple_accounts_field() {
+        const MISSING: &str = "8qbHbw2BbbTHBW1sbeqakYXVKRQM8Ne7pLK7m6CVfeR";
+
+        // Slices every account as the node would, account data being the mint layout.
+        let server = StandInRpcServer::start(|_, params| {
+            let slice = &params[1]["dataSlice"];
+            let offset = slice["offset"].as_u64().unwrap() as usize;
+            let length = slice["length"].as_u64().unwrap() as usize;
+            let data = packed_mint(6);
+            StandInResponse::accounts(params, |key| {
+                (key.to_string() != MISSING)
+                    .then(|| account_json(&spl_token::id(), &data[offset..offset + length]))
+            })
+        })
+        .await;
+        let client = JsonRpcClient::new(ClientConfig::new(EndPoint::CustomUrl(server.url())));
+        let pubkeys = vec![
+            Pubkey::new_unique(),
+            Pubkey::from_str(MISSING).unwrap(),
+            Pubkey::new_unique(),
+        ];
+
+        // Mint layout: COption<Pubkey> authority (36), supply (8), decimals (1).
+        let supplies = get_multiple_accounts_field::<u64>(&client, &pubkeys, 36)
+            .await
+            .unwrap();
+        assert_eq!(
+            supplies,
+            vec![
+                AccountResult::Found(1_000),
+                AccountResult::Missing,
+                AccountResult::Found(1_000)
+            ]
+        );
+
+        let decimals = get_account_field::<u8>(&client, &pubkeys[0], 44)
+            .await
+            .unwrap();
+        assert_eq!(decimals, 6);
+        assert_eq!(
+            get_account_field::<u8>(&client, &pubkeys[1], 44)
+                .await
+                .unwrap_err()
+                .code(),
+            "NOT_FOUND"
+        );
+
+        let request = &server.requests()[0].body;
+        assert_eq!(
+            request["params"][1]["dataSlice"],
+            json!({ "offset": 36, "length": 8 })
+        );
     }
 }
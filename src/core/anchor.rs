@@ -0,0 +1,199 @@
+use borsh::BorshDeserialize;
+use solana_sdk::{account::Account, borsh::try_from_slice_unchecked, hash::hash, pubkey::Pubkey};
+
+use crate::core::{
+    account::{get_multiple_accounts_chunked, AccountResult},
+    error::{Web3WasmError, Web3WasmResult},
+    rpc::RpcClient,
+};
+
+/// Anchor prefixes every account with this many discriminator bytes.
+pub const DISCRIMINATOR_LEN: usize = 8;
+
+/// First 8 bytes of `sha256("account:<account_name>")`, as Anchor computes them.
+pub fn account_discriminator(account_name: &str) -> [u8; DISCRIMINATOR_LEN] {
+    let mut discriminator = [0u8; DISCRIMINATOR_LEN];
+    discriminator
+        .copy_from_slice(&hash(format!("account:{account_name}").as_bytes()).to_bytes()[..8]);
+    discriminator
+}
+
+/// Checks the owner and discriminator of an Anchor account, then decodes the rest of its data.
+///
+/// Trailing bytes are ignored since Anchor accounts are often allocated bigger than needed.
+pub fn decode_anchor_account<T: BorshDeserialize>(
+    account: &Account,
+    account_name: &str,
+    program_id: &Pubkey,
+) -> Web3WasmResult<T> {
+    if account.owner != *program_id {
+        return Err(Web3WasmError::Decode(format!(
+            "Account owned by {}, expected {}",
+            account.owner, program_id
+        )));
+    }
+
+    if account.data.len() < DISCRIMINATOR_LEN
+        || account.data[..DISCRIMINATOR_LEN] != account_discriminator(account_name)
+    {
+        return Err(Web3WasmError::Decode(format!(
+            "Account is not a {account_name}: discriminator mismatch"
+        )));
+    }
+
+    Ok(try_from_slice_unchecked::<T>(
+        &account.data[DISCRIMINATOR_LEN..],
+    )?)
+}
+
+pub async fn get_and_deserialize_anchor_account<T: BorshDeserialize>(
+    client: &impl RpcClient,
+    pubkey: &Pubkey,
+    account_name: &str,
+    program_id: &Pubkey,
+) -> Web3WasmResult<T> {
+    let account = client.get_account(pubkey).await?;
+    decode_anchor_account(&account, account_name, program_id)
+}
+
+/// Batch version of [`get_and_deserialize_anchor_account`], one entry per key.
+///
+/// Accounts of another program or type come back as `AccountResult::DecodeError`.
+pub async fn get_and_deserialize_multiple_anchor_accounts<T: BorshDeserialize>(
+    client: &impl RpcClient,
+    pubkeys: &[Pubkey],
+    account_name: &str,
+    program_id: &Pubkey,
+) -> Web3WasmResult<Vec<AccountResult<T>>> {
+    let maybe_accounts = get_multiple_accounts_chunked(client, pubkeys).await?;
+    let accounts = maybe_accounts
+        .into_iter()
+        .map(|account| match account {
+            Some(account) => decode_anchor_account::<T>(&account, account_name, program_id).into(),
+            None => AccountResult::Missing,
+        })
+        .collect::<Vec<_>>();
+    Ok(accounts)
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod test {
+    use borsh::BorshSerialize;
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::{
+        core::{
+            client::{ClientConfig, EndPoint},
+            json_rpc::JsonRpcClient,
+        },
+        tests::rpc_server::{account_json, StandInResponse, StandInRpcServer},
+    };
+
+    #[derive(Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+    struct Counter {
+        authority: Pubkey,
+        count: u64,
+    }
+
+    fn anchor_data(account_name: &str, counter: &Counter) -> Vec<u8> {
+        let mut data = account_discriminator(account_name).to_vec();
+        data.extend(counter.try_to_vec().unwrap());
+        // Unused space at the end of the account.
+        data.extend([0u8; 16]);
+        data
+    }
+
+    fn account(owner: Pubkey, data: Vec<u8>) -> Account {
+        Account {
+            lamports: 1,
+            data,
+            owner,
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+
+    #[test]
+    fn test_account_discriminator() {
+        assert_eq!(
+            account_discriminator("Counter"),
+            [255, 176, 4, 245, 188, 253, 124, 25]
+        );
+    }
+
+    #[test]
+    fn test_decode_anchor_account() {
+        let program_id = Pubkey::new_unique();
+        let counter = Counter {
+            authority: Pubkey::new_unique(),
+            count: 42,
+        };
+
+        let decoded = decode_anchor_account::<Counter>(
+            &account(program_id, anchor_data("Counter", &counter)),
+            "Counter",
+            &program_id,
+        )
+        .unwrap();
+        assert_eq!(decoded, counter);
+
+        let err = decode_anchor_account::<Counter>(
+            &account(program_id, anchor_data("Vault", &counter)),
+            "Counter",
+            &program_id,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Account is not a Counter: discriminator mismatch"
+        );
+
+        let err = decode_anchor_account::<Counter>(
+            &account(Pubkey::new_unique(), anchor_data("Counter", &counter)),
+            "Counter",
+            &program_id,
+        )
+        .unwrap_err();
+        assert_eq!(err.code(), "DECODE_ERROR");
+
+        assert!(decode_anchor_account::<Counter>(
+            &account(program_id, vec![1, 2]),
+            "Counter",
+            &program_id
+        )
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_and_deserialize_multiple_anchor_accounts() {
+        let program_id = Pubkey::new_from_array([9; 32]);
+        let counter = Counter {
+            authority: Pubkey::new_from_array([1; 32]),
+            count: 7,
+        };
+        let accounts = json!([
+            account_json(&program_id, &anchor_data("Counter", &counter)),
+            Value::Null,
+            account_json(&program_id, &anchor_data("Vault", &counter)),
+        ]);
+        let server =
+            StandInRpcServer::start(move |_, _| StandInResponse::value(accounts.clone())).await;
+        let client = JsonRpcClient::new(ClientConfig::new(EndPoint::CustomUrl(server.url())));
+        let pubkeys = (0..3).map(|_| Pubkey::new_unique()).collect::<Vec<_>>();
+
+        let results = get_and_deserialize_multiple_anchor_accounts::<Counter>(
+            &client,
+            &pubkeys,
+            "Counter",
+            &program_id,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(results[0], AccountResult::Found(counter));
+        assert_eq!(results[1], AccountResult::Missing);
+        assert!(matches!(results[2], AccountResult::DecodeError(_)));
+    }
+}
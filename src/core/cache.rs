@@ -0,0 +1,744 @@
+use std::{
+    collections::{HashMap, HashSet},
+    future::Future,
+    sync::Mutex,
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use fluvio_wasm_timer::Instant;
+use futures::{
+    channel::oneshot::{self, Receiver, Sender},
+    future::join_all,
+};
+use solana_client_wasm::{
+    utils::{rpc_config::RpcKeyedAccount, rpc_filter::TokenAccountsFilter},
+    WasmClient,
+};
+use solana_extra_wasm::account_decoder::parse_token::UiTokenAccount;
+use solana_sdk::{account::Account, clock::Slot, hash::Hash, pubkey::Pubkey};
+
+use crate::core::{
+    account::DataSlice,
+    error::{RpcError, Web3WasmError, Web3WasmResult},
+    program_accounts::ProgramAccountsConfig,
+    rpc::RpcClient,
+};
+
+/// How long each kind of response stays fresh in a [`CachedClient`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheConfig {
+    /// Accounts fetched by `get_account` and `get_multiple_accounts`.
+    pub account_ttl: Duration,
+    /// Accounts owned by the Metaplex token metadata program.
+    pub metadata_ttl: Duration,
+    /// Parsed token accounts, single or by owner.
+    pub token_account_ttl: Duration,
+    pub balance_ttl: Duration,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            account_ttl: Duration::from_secs(30),
+            metadata_ttl: Duration::from_secs(600),
+            token_account_ttl: Duration::from_secs(10),
+            balance_ttl: Duration::from_secs(5),
+        }
+    }
+}
+
+impl CacheConfig {
+    fn account_ttl(&self, account: &Option<Account>) -> Duration {
+        match account {
+            Some(account) if account.owner == mpl_token_metadata::id() => self.metadata_ttl,
+            _ => self.account_ttl,
+        }
+    }
+}
+
+type Outcome<V> = Web3WasmResult<V>;
+
+enum Entry<V> {
+    Ready {
+        value: V,
+        expires_at: Instant,
+    },
+    /// A request is in flight; waiters get its outcome.
+    /// `stale` is set when the key was invalidated meanwhile, so the outcome is not kept
+    /// and later lookups start a new request.
+    Pending {
+        request: u64,
+        waiters: Vec<Sender<Outcome<V>>>,
+        stale: bool,
+    },
+}
+
+enum Lookup<V> {
+    Hit(V),
+    Wait(Receiver<Outcome<V>>),
+    /// The caller fetches the key, as this request.
+    Fetch(u64),
+}
+
+struct Entries<K, V> {
+    entries: HashMap<K, Entry<V>>,
+    /// Waiters of stale requests a newer request replaced, by request.
+    superseded: HashMap<u64, Vec<Sender<Outcome<V>>>>,
+    next_request: u64,
+}
+
+struct TtlStore<K, V> {
+    state: Mutex<Entries<K, V>>,
+}
+
+impl<K: std::hash::Hash + Eq + Clone, V: Clone> TtlStore<K, V> {
+    fn new() -> Self {
+        Self {
+            state: Mutex::new(Entries {
+                entries: HashMap::new(),
+                superseded: HashMap::new(),
+                next_request: 0,
+            }),
+        }
+    }
+
+    /// Returns the fresh value, joins the in-flight request, or marks `key` as pending
+    /// so the caller fetches it.
+    ///
+    /// A request in flight since `key` was invalidated is not joined, it may have been
+    /// answered before the change. Its waiters still get its outcome.
+    fn lookup(&self, key: &K) -> Lookup<V> {
+        let mut state = self.state.lock().unwrap();
+        let state = &mut *state;
+        match state.entries.get_mut(key) {
+            Some(Entry::Ready { value, expires_at }) if *expires_at > Instant::now() => {
+                Lookup::Hit(value.clone())
+            }
+            Some(Entry::Pending {
+                waiters,
+                stale: false,
+                ..
+            }) => {
+                let (sender, receiver) = oneshot::channel();
+                waiters.push(sender);
+                Lookup::Wait(receiver)
+            }
+            _ => {
+                let request = state.next_request;
+                state.next_request += 1;
+                let replaced = state.entries.insert(
+                    key.clone(),
+                    Entry::Pending {
+                        request,
+                        waiters: vec![],
+                        stale: false,
+                    },
+                );
+                if let Some(Entry::Pending {
+                    request: stale_request,
+                    waiters,
+                    ..
+                }) = replaced
+                {
+                    state.superseded.insert(stale_request, waiters);
+                }
+                Lookup::Fetch(request)
+            }
+        }
+    }
+
+    /// Stores a successful outcome of `request` and hands the outcome to everyone waiting
+    /// on it. Outcomes of stale or superseded requests are not stored.
+    fn complete(&self, key: &K, request: u64, outcome: Outcome<V>, ttl: Duration) {
+        let mut state = self.state.lock().unwrap();
+        let (waiters, stale) = match state.entries.remove(key) {
+            Some(Entry::Pending {
+                request: pending,
+                waiters,
+                stale,
+            }) if pending == request => (waiters, stale),
+            other => {
+                if let Some(other) = other {
+                    state.entries.insert(key.clone(), other);
+                }
+                let waiters = state.superseded.remove(&request).unwrap_or_default();
+                (waiters, true)
+            }
+        };
+
+        if let (Ok(value), false) = (&outcome, stale) {
+            state.entries.insert(
+                key.clone(),
+                Entry::Ready {
+                    value: value.clone(),
+                    expires_at: Instant::now() + ttl,
+                },
+            );
+        }
+
+        for waiter in waiters {
+            waiter.send(outcome.clone()).ok();
+        }
+    }
+
+    /// Drops `request` when it was cancelled; its waiters see the cancellation.
+    fn abandon(&self, key: &K, request: u64) {
+        let mut state = self.state.lock().unwrap();
+        match state.entries.get(key) {
+            Some(Entry::Pending {
+                request: pending, ..
+            }) if *pending == request => {
+                state.entries.remove(key);
+            }
+            _ => {
+                state.superseded.remove(&request);
+            }
+        }
+    }
+
+    fn invalidate_where(&self, predicate: impl Fn(&K) -> bool) {
+        let mut state = self.state.lock().unwrap();
+        state.entries.retain(|key, entry| match entry {
+            Entry::Pending { stale, .. } => {
+                *stale = *stale || predicate(key);
+                true
+            }
+            Entry::Ready { .. } => !predicate(key),
+        });
+    }
+
+    fn purge_expired(&self) {
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap();
+        state.entries.retain(|_, entry| match entry {
+            Entry::Ready { expires_at, .. } => *expires_at > now,
+            Entry::Pending { .. } => true,
+        });
+    }
+
+    fn len(&self) -> usize {
+        self.state
+            .lock()
+            .unwrap()
+            .entries
+            .values()
+            .filter(|entry| matches!(entry, Entry::Ready { .. }))
+            .count()
+    }
+}
+
+/// Abandons the requests still pending when a fetch is dropped before completing.
+struct PendingGuard<'a, K: std::hash::Hash + Eq + Clone, V: Clone> {
+    store: &'a TtlStore<K, V>,
+    /// Keys with the request fetching them.
+    requests: Vec<(K, u64)>,
+}
+
+impl<K: std::hash::Hash + Eq + Clone, V: Clone> PendingGuard<'_, K, V> {
+    /// Completes every request, `outcomes` in the order of `requests`.
+    fn complete_all(mut self, outcomes: Vec<(Outcome<V>, Duration)>) {
+        let requests = std::mem::take(&mut self.requests);
+        for ((key, request), (outcome, ttl)) in requests.into_iter().zip(outcomes) {
+            self.store.complete(&key, request, outcome, ttl);
+        }
+    }
+}
+
+impl<K: std::hash::Hash + Eq + Clone, V: Clone> Drop for PendingGuard<'_, K, V> {
+    fn drop(&mut self) {
+        for (key, request) in &self.requests {
+            self.store.abandon(key, *request);
+        }
+    }
+}
+
+async fn wait<V>(receiver: Receiver<Outcome<V>>) -> Web3WasmResult<V> {
+    match receiver.await {
+        Ok(outcome) => outcome,
+        Err(_) => Err(RpcError::Transport("Cached request was cancelled".to_owned()).into()),
+    }
+}
+
+async fn cached<K, V, F>(
+    store: &TtlStore<K, V>,
+    key: K,
+    ttl: impl Fn(&V) -> Duration,
+    fetch: F,
+) -> Web3WasmResult<V>
+where
+    K: std::hash::Hash + Eq + Clone,
+    V: Clone,
+    F: Future<Output = Web3WasmResult<V>>,
+{
+    match store.lookup(&key) {
+        Lookup::Hit(value) => Ok(value),
+        Lookup::Wait(receiver) => wait(receiver).await,
+        Lookup::Fetch(request) => {
+            let guard = PendingGuard {
+                store,
+                requests: vec![(key, request)],
+            };
+            let result = fetch.await;
+            let outcome = match &result {
+                Ok(value) => (Ok(value.clone()), ttl(value)),
+                Err(err) => (Err(err.clone()), Duration::ZERO),
+            };
+            guard.complete_all(vec![outcome]);
+            result
+        }
+    }
+}
+
+/// Looks every key up at once and fetches only the misses, in one call.
+async fn cached_many<K, V, F, Fut>(
+    store: &TtlStore<K, V>,
+    keys: &[K],
+    ttl: impl Fn(&V) -> Duration,
+    fetch: F,
+) -> Web3WasmResult<Vec<V>>
+where
+    K: std::hash::Hash + Eq + Clone,
+    V: Clone,
+    F: FnOnce(Vec<K>) -> Fut,
+    Fut: Future<Output = Web3WasmResult<Vec<V>>>,
+{
+    let mut values = HashMap::new();
+    let mut waiting = vec![];
+    let mut misses = vec![];
+
+    let mut seen = HashSet::new();
+    for key in keys.iter().filter(|&key| seen.insert(key)) {
+        match store.lookup(key) {
+            Lookup::Hit(value) => {
+                values.insert(key.clone(), value);
+            }
+            Lookup::Wait(receiver) => waiting.push((key.clone(), receiver)),
+            Lookup::Fetch(request) => misses.push((key.clone(), request)),
+        }
+    }
+
+    if !misses.is_empty() {
+        let miss_keys = misses
+            .iter()
+            .map(|(key, _)| key.clone())
+            .collect::<Vec<_>>();
+        let guard = PendingGuard {
+            store,
+            requests: misses,
+        };
+        let fetched = match fetch(miss_keys.clone()).await {
+            Ok(fetched) if fetched.len() == miss_keys.len() => Ok(fetched),
+            Ok(fetched) => Err(Web3WasmError::Decode(format!(
+                "Expected {} accounts, received {}",
+                miss_keys.len(),
+                fetched.len()
+            ))),
+            Err(err) => Err(err),
+        };
+
+        match fetched {
+            Ok(fetched) => {
+                guard.complete_all(
+                    fetched
+                        .iter()
+                        .map(|value| (Ok(value.clone()), ttl(value)))
+                        .collect(),
+                );
+                values.extend(miss_keys.into_iter().zip(fetched));
+            }
+            Err(err) => {
+                guard.complete_all(
+                    miss_keys
+                        .iter()
+                        .map(|_| (Err(err.clone()), Duration::ZERO))
+                        .collect(),
+                );
+                return Err(err);
+            }
+        }
+    }
+
+    let (waiting_keys, receivers): (Vec<_>, Vec<_>) = waiting.into_iter().unzip();
+    for (key, value) in waiting_keys
+        .into_iter()
+        .zip(join_all(receivers.into_iter().map(wait)).await)
+    {
+        values.insert(key, value?);
+    }
+
+    Ok(keys.iter().map(|key| values[key].clone()).collect())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum FilterKey {
+    Mint(Pubkey),
+    ProgramId(Pubkey),
+}
+
+impl From<&TokenAccountsFilter> for FilterKey {
+    fn from(filter: &TokenAccountsFilter) -> Self {
+        match filter {
+            TokenAccountsFilter::Mint(mint) => FilterKey::Mint(*mint),
+            TokenAccountsFilter::ProgramId(program_id) => FilterKey::ProgramId(*program_id),
+        }
+    }
+}
+
+/// In-memory TTL cache in front of another client.
+///
+/// Concurrent requests for the same key share one in-flight RPC call, and batch calls
+/// only fetch the keys that are not cached yet. Call `invalidate` for the accounts a
+/// transaction touched, or `invalidate_all`, to see its effects before the TTL expires.
+/// Sliced account data, `get_program_accounts`, `get_slot`, `get_health` and
+/// `get_genesis_hash` are never cached.
+pub struct CachedClient<C = WasmClient> {
+    inner: C,
+    config: CacheConfig,
+    accounts: TtlStore<Pubkey, Option<Account>>,
+    token_accounts: TtlStore<Pubkey, Option<UiTokenAccount>>,
+    token_accounts_by_owner: TtlStore<(Pubkey, FilterKey), Vec<RpcKeyedAccount>>,
+    balances: TtlStore<Pubkey, u64>,
+}
+
+impl<C: RpcClient> CachedClient<C> {
+    pub fn new(inner: C, config: CacheConfig) -> Self {
+        Self {
+            inner,
+            config,
+            accounts: TtlStore::new(),
+            token_accounts: TtlStore::new(),
+            token_accounts_by_owner: TtlStore::new(),
+            balances: TtlStore::new(),
+        }
+    }
+
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    pub fn config(&self) -> &CacheConfig {
+        &self.config
+    }
+
+    /// Forgets everything cached for `pubkey`, as an account or as a token owner.
+    pub fn invalidate(&self, pubkey: &Pubkey) {
+        self.accounts.invalidate_where(|key| key == pubkey);
+        self.token_accounts.invalidate_where(|key| key == pubkey);
+        self.token_accounts_by_owner
+            .invalidate_where(|(owner, _)| owner == pubkey);
+        self.balances.invalidate_where(|key| key == pubkey);
+    }
+
+    pub fn invalidate_all(&self) {
+        self.accounts.invalidate_where(|_| true);
+        self.token_accounts.invalidate_where(|_| true);
+        self.token_accounts_by_owner.invalidate_where(|_| true);
+        self.balances.invalidate_where(|_| true);
+    }
+
+    /// Frees the memory held by expired entries.
+    pub fn purge_expired(&self) {
+        self.accounts.purge_expired();
+        self.token_accounts.purge_expired();
+        self.token_accounts_by_owner.purge_expired();
+        self.balances.purge_expired();
+    }
+
+    /// Number of cached responses, expired ones included until purged.
+    pub fn len(&self) -> usize {
+        self.accounts.len()
+            + self.token_accounts.len()
+            + self.token_accounts_by_owner.len()
+            + self.balances.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<C: RpcClient> RpcClient for CachedClient<C> {
+    async fn get_account(&self, pubkey: &Pubkey) -> Web3WasmResult<Account> {
+        let account = cached(
+            &self.accounts,
+            *pubkey,
+            |account| self.config.account_ttl(account),
+            async { Ok::<_, Web3WasmError>(Some(self.inner.get_account(pubkey).await?)) },
+        )
+        .await?;
+
+        match account {
+            Some(account) => Ok(account),
+            None => Err(Web3WasmError::NotFound(format!(
+                "AccountNotFound: pubkey={}",
+                pubkey
+            ))),
+        }
+    }
+
+    async fn get_multiple_accounts(
+        &self,
+        pubkeys: &[Pubkey],
+    ) -> Web3WasmResult<Vec<Option<Account>>> {
+        cached_many(
+            &self.accounts,
+            pubkeys,
+            |account| self.config.account_ttl(account),
+            |misses| async move { self.inner.get_multiple_accounts(&misses).await },
+        )
+        .await
+    }
+
+    async fn get_multiple_accounts_data_slice(
+        &self,
+        pubkeys: &[Pubkey],
+        slice: DataSlice,
+    ) -> Web3WasmResult<Vec<Option<Account>>> {
+        self.inner
+            .get_multiple_accounts_data_slice(pubkeys, slice)
+            .await
+    }
+
+    async fn get_multiple_token_accounts(
+        &self,
+        pubkeys: &[Pubkey],
+    ) -> Web3WasmResult<Vec<Option<UiTokenAccount>>> {
+        cached_many(
+            &self.token_accounts,
+            pubkeys,
+            |_| self.config.token_account_ttl,
+            |misses| async move { self.inner.get_multiple_token_accounts(&misses).await },
+        )
+        .await
+    }
+
+    async fn get_token_accounts_by_owner(
+        &self,
+        owner: &Pubkey,
+        filter: &TokenAccountsFilter,
+    ) -> Web3WasmResult<Vec<RpcKeyedAccount>> {
+        cached(
+            &self.token_accounts_by_owner,
+            (*owner, FilterKey::from(filter)),
+            |_| self.config.token_account_ttl,
+            self.inner.get_token_accounts_by_owner(owner, filter),
+        )
+        .await
+    }
+
+    async fn get_program_accounts(
+        &self,
+        program_id: &Pubkey,
+        config: &ProgramAccountsConfig,
+    ) -> Web3WasmResult<Vec<(Pubkey, Account)>> {
+        self.inner.get_program_accounts(program_id, config).await
+    }
+
+    async fn get_balance(&self, pubkey: &Pubkey) -> Web3WasmResult<u64> {
+        cached(
+            &self.balances,
+            *pubkey,
+            |_| self.config.balance_ttl,
+            self.inner.get_balance(pubkey),
+        )
+        .await
+    }
+
+    async fn get_slot(&self) -> Web3WasmResult<Slot> {
+        self.inner.get_slot().await
+    }
+
+    async fn get_health(&self) -> Web3WasmResult<()> {
+        self.inner.get_health().await
+    }
+
+    async fn get_genesis_hash(&self) -> Web3WasmResult<Hash> {
+        self.inner.get_genesis_hash().await
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod test {
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::{
+        core::{
+            client::{ClientConfig, EndPoint},
+            json_rpc::JsonRpcClient,
+        },
+        tests::rpc_server::{account_json, StandInResponse, StandInRpcServer},
+    };
+
+    /// Serves every requested account, owned by the metadata program when the key says so.
+    async fn account_server(metadata_key: Pubkey) -> StandInRpcServer {
+        StandInRpcServer::start(move |method, params| {
+            let owner = |key: &Value| {
+                if key.as_str() == Some(metadata_key.to_string().as_str()) {
+                    mpl_token_metadata::id()
+                } else {
+                    Pubkey::default()
+                }
+            };
+            let value = match method {
+                "getAccountInfo" => account_json(&owner(&params[0]), &[]),
+                "getMultipleAccounts" => params[0]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|key| account_json(&owner(key), &[]))
+                    .collect(),
+                "getBalance" => json!(42),
+                _ => return StandInResponse::rpc_error(-32601, "Method not found"),
+            };
+            StandInResponse::value(value)
+        })
+        .await
+    }
+
+    fn cached_client(
+        server: &StandInRpcServer,
+        config: CacheConfig,
+    ) -> CachedClient<JsonRpcClient> {
+        CachedClient::new(
+            JsonRpcClient::new(ClientConfig::new(EndPoint::CustomUrl(server.url()))),
+            config,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_cache_hit_and_invalidate() {
+        let server = account_server(Pubkey::new_unique()).await;
+        let client = cached_client(&server, CacheConfig::default());
+        let pubkey = Pubkey::new_unique();
+
+        client.get_account(&pubkey).await.unwrap();
+        client.get_account(&pubkey).await.unwrap();
+        assert_eq!(server.request_count("getAccountInfo"), 1);
+
+        client.invalidate(&pubkey);
+        client.get_account(&pubkey).await.unwrap();
+        assert_eq!(server.request_count("getAccountInfo"), 2);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_requests_are_deduplicated() {
+        let server = account_server(Pubkey::new_unique()).await;
+        let client = cached_client(&server, CacheConfig::default());
+        let pubkey = Pubkey::new_unique();
+
+        let balances = join_all((0..5).map(|_| client.get_balance(&pubkey))).await;
+
+        assert!(balances.into_iter().all(|balance| balance.unwrap() == 42));
+        assert_eq!(server.request_count("getBalance"), 1);
+    }
+
+    #[tokio::test]
+    async fn test_multiple_accounts_fetch_only_misses() {
+        let server = account_server(Pubkey::new_unique()).await;
+        let client = cached_client(&server, CacheConfig::default());
+        let keys = (0..3).map(|_| Pubkey::new_unique()).collect::<Vec<_>>();
+
+        client.get_multiple_accounts(&keys[..2]).await.unwrap();
+        let accounts = client.get_multiple_accounts(&keys).await.unwrap();
+
+        assert_eq!(accounts.len(), 3);
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[1].body["params"][0], json!([keys[2].to_string()]));
+
+        // Singles share the same entries.
+        client.get_account(&keys[0]).await.unwrap();
+        assert_eq!(server.request_count("getAccountInfo"), 0);
+    }
+
+    #[tokio::test]
+    async fn test_metadata_outlives_other_accounts() {
+        let metadata_key = Pubkey::new_unique();
+        let server = account_server(metadata_key).await;
+        let client = cached_client(
+            &server,
+            CacheConfig {
+                account_ttl: Duration::ZERO,
+                ..CacheConfig::default()
+            },
+        );
+        let keys = [metadata_key, Pubkey::new_unique()];
+
+        client.get_multiple_accounts(&keys).await.unwrap();
+        client.get_multiple_accounts(&keys).await.unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[1].body["params"][0], json!([keys[1].to_string()]));
+    }
+
+    #[tokio::test]
+    async fn test_balance_expires() {
+        let server = account_server(Pubkey::new_unique()).await;
+        let client = cached_client(
+            &server,
+            CacheConfig {
+                balance_ttl: Duration::from_millis(50),
+                ..CacheConfig::default()
+            },
+        );
+        let pubkey = Pubkey::new_unique();
+
+        client.get_balance(&pubkey).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(80)).await;
+        client.get_balance(&pubkey).await.unwrap();
+
+        assert_eq!(server.request_count("getBalance"), 2);
+    }
+
+    #[tokio::test]
+    async fn test_invalidate_during_fetch() {
+        let store = TtlStore::<u8, u64>::new();
+        let ttl = |_: &u64| Duration::from_secs(60);
+        let (sender, receiver) = oneshot::channel::<u64>();
+
+        let mut in_flight = Box::pin(cached(&store, 1, ttl, async {
+            Ok::<_, Web3WasmError>(receiver.await.unwrap())
+        }));
+        assert!(futures::poll!(&mut in_flight).is_pending());
+        let mut joined = Box::pin(cached(&store, 1, ttl, async { Ok::<_, Web3WasmError>(0) }));
+        assert!(futures::poll!(&mut joined).is_pending());
+
+        // A transaction changed the account: the next read must not join the old request.
+        store.invalidate_where(|_| true);
+        let fresh = cached(&store, 1, ttl, async { Ok::<_, Web3WasmError>(2) }).await;
+        assert_eq!(fresh.unwrap(), 2);
+
+        // Callers of the old request still get its outcome, which is not stored.
+        sender.send(1).unwrap();
+        assert_eq!(in_flight.await.unwrap(), 1);
+        assert_eq!(joined.await.unwrap(), 1);
+        let cached_value = cached(&store, 1, ttl, async { Ok::<_, Web3WasmError>(0) }).await;
+        assert_eq!(cached_value.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_errors_are_shared_but_not_cached() {
+        let server =
+            StandInRpcServer::start(|_, _| StandInResponse::rpc_error(-32602, "Invalid param"))
+                .await;
+        let client = cached_client(&server, CacheConfig::default());
+        let pubkey = Pubkey::new_unique();
+
+        let results = join_all((0..3).map(|_| client.get_balance(&pubkey))).await;
+        assert!(results
+            .iter()
+            .all(|result| result.as_ref().unwrap_err().to_string()
+                == "RPC error -32602: Invalid param"));
+        assert_eq!(server.request_count("getBalance"), 1);
+
+        client.get_balance(&pubkey).await.unwrap_err();
+        assert_eq!(server.request_count("getBalance"), 2);
+        assert!(client.is_empty());
+    }
+}
@@ -1,9 +1,16 @@
+use std::{str::FromStr, time::Duration};
+
 use solana_client_wasm::WasmClient;
-use solana_sdk::commitment_config::{CommitmentConfig, CommitmentLevel};
+use solana_sdk::{
+    commitment_config::{CommitmentConfig, CommitmentLevel},
+    hash::Hash,
+};
 
 use strum_macros::{Display, EnumString};
 
-#[derive(EnumString, Display, Debug)]
+use crate::core::{error::Web3WasmResult, rpc::RpcClient};
+
+#[derive(EnumString, Display, Debug, Clone, PartialEq, Eq)]
 pub enum EndPoint {
     #[strum(serialize = "https://api.mainnet-beta.solana.com")]
     Mainnet,
@@ -11,22 +18,228 @@ pub enum EndPoint {
     Devnet,
     #[strum(serialize = "https://api.testnet.solana.com")]
     Testnet,
+    /// `solana-test-validator` defaults.
+    #[strum(serialize = "http://127.0.0.1:8899")]
+    Localnet,
     #[strum(disabled)]
     CustomUrl(String),
 }
 
-#[derive(EnumString, Display, Debug)]
+impl EndPoint {
+    pub fn url(&self) -> String {
+        match self {
+            EndPoint::CustomUrl(url) => url.to_string(),
+            endpoint => endpoint.to_string(),
+        }
+    }
+
+    /// Websocket URL for subscriptions: `http(s)` becomes `ws(s)`, and the
+    /// `solana-test-validator` RPC port 8899 becomes its websocket port 8900.
+    ///
+    /// Other ports are kept, providers serving websockets elsewhere need
+    /// `ClientConfigBuilder::ws_url`.
+    pub fn ws_url(&self) -> String {
+        let url = self.url();
+        let (scheme, rest) = match url.split_once("://") {
+            Some(("https", rest)) => ("wss", rest),
+            Some(("http", rest)) => ("ws", rest),
+            _ => return url,
+        };
+
+        let (authority, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+        let authority = match authority.rsplit_once(':') {
+            Some((host, "8899")) => format!("{host}:8900"),
+            _ => authority.to_owned(),
+        };
+
+        format!("{scheme}://{authority}{path}")
+    }
+
+    /// The cluster of a well-known endpoint, `None` for `CustomUrl`,
+    /// see `detect_cluster` for those.
+    pub fn cluster_id(&self) -> Option<ClusterId> {
+        match self {
+            EndPoint::Mainnet => Some(ClusterId::Mainnet),
+            EndPoint::Devnet => Some(ClusterId::Devnet),
+            EndPoint::Testnet => Some(ClusterId::Testnet),
+            EndPoint::Localnet => Some(ClusterId::Localnet),
+            EndPoint::CustomUrl(_) => None,
+        }
+    }
+}
+
+impl From<ClusterId> for EndPoint {
+    fn from(cluster_id: ClusterId) -> Self {
+        match cluster_id {
+            ClusterId::Mainnet => EndPoint::Mainnet,
+            ClusterId::Devnet => EndPoint::Devnet,
+            ClusterId::Testnet => EndPoint::Testnet,
+            ClusterId::Localnet => EndPoint::Localnet,
+        }
+    }
+}
+
+pub const MAINNET_GENESIS_HASH: &str = "5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d";
+pub const DEVNET_GENESIS_HASH: &str = "EtWTRABZaYq6iMfeYKouRu166VU2xqa1wcaWoxPkrZBG";
+pub const TESTNET_GENESIS_HASH: &str = "4uhcVJyU9pJkvQyS88uRDiswHXSCkY3zQawwpjk2NsNY";
+
+#[derive(EnumString, Display, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ClusterId {
-    #[strum(serialize = "mainnet-beta")]
+    #[strum(to_string = "mainnet-beta", serialize = "mainnet")]
     Mainnet,
     #[strum(serialize = "devnet")]
     Devnet,
     #[strum(serialize = "testnet")]
     Testnet,
+    #[strum(serialize = "localnet")]
+    Localnet,
+}
+
+impl ClusterId {
+    /// `None` for `Localnet`, every test validator has its own.
+    pub fn genesis_hash(&self) -> Option<Hash> {
+        let genesis_hash = match self {
+            ClusterId::Mainnet => MAINNET_GENESIS_HASH,
+            ClusterId::Devnet => DEVNET_GENESIS_HASH,
+            ClusterId::Testnet => TESTNET_GENESIS_HASH,
+            ClusterId::Localnet => return None,
+        };
+        Hash::from_str(genesis_hash).ok()
+    }
+
+    /// Any genesis hash but the public clusters' is taken for a local or private cluster.
+    pub fn from_genesis_hash(genesis_hash: &Hash) -> Self {
+        [ClusterId::Mainnet, ClusterId::Devnet, ClusterId::Testnet]
+            .into_iter()
+            .find(|cluster_id| cluster_id.genesis_hash().as_ref() == Some(genesis_hash))
+            .unwrap_or(ClusterId::Localnet)
+    }
+
+    pub fn explorer_address_url(&self, address: &str) -> String {
+        self.explorer_url(&format!("address/{address}"))
+    }
+
+    pub fn explorer_tx_url(&self, signature: &str) -> String {
+        self.explorer_url(&format!("tx/{signature}"))
+    }
+
+    fn explorer_url(&self, path: &str) -> String {
+        match self {
+            ClusterId::Mainnet => format!("https://explorer.solana.com/{path}"),
+            ClusterId::Localnet => format!(
+                "https://explorer.solana.com/{path}?cluster=custom&customUrl=http%3A%2F%2F127.0.0.1%3A8899"
+            ),
+            cluster_id => format!("https://explorer.solana.com/{path}?cluster={cluster_id}"),
+        }
+    }
+}
+
+/// Asks the node for its genesis hash to tell which cluster it serves.
+pub async fn detect_cluster(client: &impl RpcClient) -> Web3WasmResult<ClusterId> {
+    let genesis_hash = client.get_genesis_hash().await?;
+    Ok(ClusterId::from_genesis_hash(&genesis_hash))
+}
+
+/// Connection settings shared by `WasmClient`, `JsonRpcClient`, `NftInformation`
+/// and every `WalletInformation` implementor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientConfig {
+    pub endpoint: EndPoint,
+    pub commitment: CommitmentLevel,
+    pub timeout: Option<Duration>,
+    pub headers: Vec<(String, String)>,
+    pub bearer_token: Option<String>,
+    /// Overrides the websocket URL derived from `endpoint`.
+    pub ws_url: Option<String>,
+}
+
+impl ClientConfig {
+    pub fn new(endpoint: EndPoint) -> Self {
+        Self {
+            endpoint,
+            commitment: CommitmentLevel::Confirmed,
+            timeout: None,
+            headers: vec![],
+            bearer_token: None,
+            ws_url: None,
+        }
+    }
+
+    pub fn builder(endpoint: EndPoint) -> ClientConfigBuilder {
+        ClientConfigBuilder {
+            config: Self::new(endpoint),
+        }
+    }
+
+    pub fn url(&self) -> String {
+        self.endpoint.url()
+    }
+
+    /// `ws_url` when set, otherwise [`EndPoint::ws_url`].
+    pub fn ws_url(&self) -> String {
+        self.ws_url
+            .clone()
+            .unwrap_or_else(|| self.endpoint.ws_url())
+    }
+
+    pub fn commitment_config(&self) -> CommitmentConfig {
+        CommitmentConfig {
+            commitment: self.commitment,
+        }
+    }
+
+    /// Extra headers plus `Authorization: Bearer <token>` when a token is set.
+    pub fn http_headers(&self) -> Vec<(String, String)> {
+        let mut headers = self.headers.clone();
+        if let Some(token) = &self.bearer_token {
+            headers.push(("Authorization".to_owned(), format!("Bearer {token}")));
+        }
+        headers
+    }
+}
+
+pub struct ClientConfigBuilder {
+    config: ClientConfig,
+}
+
+impl ClientConfigBuilder {
+    pub fn commitment(mut self, commitment: CommitmentLevel) -> Self {
+        self.config.commitment = commitment;
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.config.timeout = Some(timeout);
+        self
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.config
+            .headers
+            .push((name.to_owned(), value.to_owned()));
+        self
+    }
+
+    pub fn bearer_token(mut self, token: &str) -> Self {
+        self.config.bearer_token = Some(token.to_owned());
+        self
+    }
+
+    pub fn ws_url(mut self, url: &str) -> Self {
+        self.config.ws_url = Some(url.to_owned());
+        self
+    }
+
+    pub fn build(self) -> ClientConfig {
+        self.config
+    }
 }
 
 pub trait Web3WasmClient {
     fn new(endpoint: &EndPoint) -> Self;
+    /// Only `endpoint` and `commitment` apply to `WasmClient`,
+    /// use `JsonRpcClient` for timeouts, headers and bearer tokens.
+    fn new_with_config(config: &ClientConfig) -> Self;
     fn new_mainnet() -> Self;
     fn new_devnet() -> Self;
     fn new_testnet() -> Self;
@@ -34,19 +247,11 @@ pub trait Web3WasmClient {
 
 impl Web3WasmClient for WasmClient {
     fn new(endpoint: &EndPoint) -> Self {
-        let endpoint = match endpoint {
-            EndPoint::Mainnet => EndPoint::Mainnet.to_string(),
-            EndPoint::Devnet => EndPoint::Devnet.to_string(),
-            EndPoint::Testnet => EndPoint::Testnet.to_string(),
-            EndPoint::CustomUrl(url) => url.to_string(),
-        };
+        Web3WasmClient::new_with_config(&ClientConfig::new(endpoint.clone()))
+    }
 
-        WasmClient::new_with_commitment(
-            endpoint.as_ref(),
-            CommitmentConfig {
-                commitment: CommitmentLevel::Confirmed,
-            },
-        )
+    fn new_with_config(config: &ClientConfig) -> Self {
+        WasmClient::new_with_commitment(config.url().as_ref(), config.commitment_config())
     }
 
     fn new_mainnet() -> Self {
@@ -61,3 +266,127 @@ impl Web3WasmClient for WasmClient {
         Web3WasmClient::new(&EndPoint::Testnet)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_client_config_builder() {
+        let config = ClientConfig::builder(EndPoint::Devnet)
+            .commitment(CommitmentLevel::Finalized)
+            .timeout(Duration::from_secs(5))
+            .header("x-api-key", "key")
+            .bearer_token("token")
+            .build();
+
+        assert_eq!(config.url(), "https://api.devnet.solana.com");
+        assert_eq!(config.commitment_config(), CommitmentConfig::finalized());
+        assert_eq!(config.timeout, Some(Duration::from_secs(5)));
+        assert_eq!(
+            config.http_headers(),
+            vec![
+                ("x-api-key".to_owned(), "key".to_owned()),
+                ("Authorization".to_owned(), "Bearer token".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_client_config_defaults() {
+        let config = ClientConfig::new(EndPoint::CustomUrl("http://localhost:8899".to_owned()));
+
+        assert_eq!(config.url(), "http://localhost:8899");
+        assert_eq!(config.commitment, CommitmentLevel::Confirmed);
+        assert!(config.http_headers().is_empty());
+    }
+
+    #[test]
+    fn test_cluster_id_conversions() {
+        assert_eq!(EndPoint::Localnet.url(), "http://127.0.0.1:8899");
+        assert_eq!(EndPoint::Localnet.ws_url(), "ws://127.0.0.1:8900");
+        assert_eq!(
+            EndPoint::CustomUrl("https://rpc.example.com:443/key".to_owned()).ws_url(),
+            "wss://rpc.example.com:443/key"
+        );
+        assert_eq!(
+            EndPoint::Mainnet.ws_url(),
+            "wss://api.mainnet-beta.solana.com"
+        );
+        let config = ClientConfig::builder(EndPoint::Mainnet)
+            .ws_url("wss://ws.example.com")
+            .build();
+        assert_eq!(config.ws_url(), "wss://ws.example.com");
+        for cluster_id in [
+            ClusterId::Mainnet,
+            ClusterId::Devnet,
+            ClusterId::Testnet,
+            ClusterId::Localnet,
+        ] {
+            assert_eq!(EndPoint::from(cluster_id).cluster_id(), Some(cluster_id));
+        }
+        assert_eq!(
+            EndPoint::CustomUrl("http://rpc".to_owned()).cluster_id(),
+            None
+        );
+
+        assert_eq!(ClusterId::from_str("mainnet").unwrap(), ClusterId::Mainnet);
+        assert_eq!(ClusterId::Mainnet.to_string(), "mainnet-beta");
+    }
+
+    #[test]
+    fn test_cluster_from_genesis_hash() {
+        let devnet = Hash::from_str(DEVNET_GENESIS_HASH).unwrap();
+
+        assert_eq!(ClusterId::from_genesis_hash(&devnet), ClusterId::Devnet);
+        assert_eq!(
+            ClusterId::from_genesis_hash(&Hash::new_unique()),
+            ClusterId::Localnet
+        );
+    }
+
+    #[test]
+    fn test_explorer_urls() {
+        assert_eq!(
+            ClusterId::Mainnet.explorer_tx_url("sig"),
+            "https://explorer.solana.com/tx/sig"
+        );
+        assert_eq!(
+            ClusterId::Devnet.explorer_address_url("addr"),
+            "https://explorer.solana.com/address/addr?cluster=devnet"
+        );
+    }
+
+    #[tokio::test]
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn test_detect_cluster() {
+        use crate::core::{json_rpc::JsonRpcClient, transport::MockTransport};
+
+        let client = JsonRpcClient::with_transport(
+            MockTransport::default().with_result("getGenesisHash", TESTNET_GENESIS_HASH.into()),
+            CommitmentLevel::Confirmed,
+        );
+
+        assert_eq!(detect_cluster(&client).await.unwrap(), ClusterId::Testnet);
+    }
+
+    #[test]
+    fn test_ws_url() {
+        assert_eq!(
+            EndPoint::Mainnet.ws_url(),
+            "wss://api.mainnet-beta.solana.com"
+        );
+        assert_eq!(
+            EndPoint::CustomUrl("http://127.0.0.1:8899".to_owned()).ws_url(),
+            "ws://127.0.0.1:8900"
+        );
+        assert_eq!(
+            EndPoint::CustomUrl("https://rpc.example.com/v1/key".to_owned()).ws_url(),
+            "wss://rpc.example.com/v1/key"
+        );
+        assert_eq!(
+            EndPoint::CustomUrl("ws://127.0.0.1:1234".to_owned()).ws_url(),
+            "ws://127.0.0.1:1234"
+        );
+    }
+}
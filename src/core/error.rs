@@ -0,0 +1,257 @@
+use std::{fmt::Display, time::Duration};
+
+use solana_sdk::{program_error::ProgramError, pubkey::ParsePubkeyError};
+use thiserror::Error;
+
+/// JSON-RPC code of a node that is behind or otherwise unhealthy.
+pub const NODE_UNHEALTHY_CODE: i64 = -32005;
+
+// Errors -------------------------------------
+
+/// Why a call to an RPC node failed.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum RpcError {
+    /// Non-2xx HTTP response.
+    #[error("HTTP error {status}: {body}")]
+    HttpStatus {
+        status: u16,
+        /// Parsed from the `Retry-After` header when it holds a number of seconds.
+        retry_after: Option<Duration>,
+        body: String,
+    },
+    /// JSON-RPC `error` returned by the node.
+    #[error("RPC error {code}: {message}")]
+    JsonRpc { code: i64, message: String },
+    #[error("Request timed out after {}ms", .0.as_millis())]
+    Timeout(Duration),
+    /// Connection failures and other transport errors.
+    #[error("Transport error: {0}")]
+    Transport(String),
+    /// Error reported by `WasmClient` that is neither an HTTP status, a JSON-RPC error
+    /// nor a failed request, only known by its message.
+    #[error("{0}")]
+    Client(String),
+}
+
+/// Status of a reqwest status error, `HTTP status server error (503 Service Unavailable) for url (...)`.
+fn parse_http_status(message: &str) -> Option<u16> {
+    let (_, rest) = message.split_once("HTTP status ")?;
+    let rest = rest
+        .strip_prefix("client error (")
+        .or_else(|| rest.strip_prefix("server error ("))?;
+    rest.get(..3)?.parse().ok()
+}
+
+/// Code and message of a Solana client RPC error, `RPC response error -32005: Node is behind ...`.
+fn parse_rpc_response_error(message: &str) -> Option<(i64, &str)> {
+    let (_, rest) = message.split_once("RPC response error ")?;
+    let (code, message) = rest.split_once(':')?;
+    Some((code.parse().ok()?, message.trim()))
+}
+
+impl RpcError {
+    /// Classifies an error of `WasmClient` by the HTTP status or RPC code its message
+    /// carries, or by the reqwest failure kind for requests that never got a response.
+    pub(crate) fn from_client_message(message: String) -> Self {
+        if let Some(status) = parse_http_status(&message) {
+            return RpcError::HttpStatus {
+                status,
+                retry_after: None,
+                body: message,
+            };
+        }
+        if let Some((code, message)) = parse_rpc_response_error(&message) {
+            return RpcError::JsonRpc {
+                code,
+                message: message.to_owned(),
+            };
+        }
+        if message.contains("error sending request for url")
+            || message.contains("operation timed out")
+        {
+            return RpcError::Transport(message);
+        }
+        RpcError::Client(message)
+    }
+
+    /// Returns `true` for errors that another attempt, or another endpoint, may not hit:
+    /// rate limiting, gateway errors, unhealthy nodes, timeouts and failed connections.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            RpcError::HttpStatus { status, .. } => matches!(status, 429 | 502 | 503 | 504),
+            RpcError::JsonRpc { code, .. } => *code == 429 || *code == NODE_UNHEALTHY_CODE,
+            RpcError::Timeout(_) | RpcError::Transport(_) => true,
+            // Anything `client` could classify is not a `Client` error anymore.
+            RpcError::Client(_) => false,
+        }
+    }
+}
+
+/// Error returned by every fallible function of this crate.
+///
+/// `code()` gives a stable string per variant; on the wasm side the error converts
+/// into a JS `Error` carrying that code, see `From<Web3WasmError> for JsValue`.
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum Web3WasmError {
+    #[error(transparent)]
+    Rpc(#[from] RpcError),
+    /// The account, or whatever was looked up, does not exist.
+    #[error("{0}")]
+    NotFound(String),
+    /// Data came back but could not be parsed.
+    #[error("{0}")]
+    Decode(String),
+    /// Arguments given by the caller are invalid.
+    #[error("{0}")]
+    InvalidInput(String),
+    /// A Solana program or instruction builder refused the request.
+    #[error("{0}")]
+    Program(String),
+}
+
+pub type Web3WasmResult<T> = Result<T, Web3WasmError>;
+
+impl Web3WasmError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Web3WasmError::Rpc(_) => "RPC_ERROR",
+            Web3WasmError::NotFound(_) => "NOT_FOUND",
+            Web3WasmError::Decode(_) => "DECODE_ERROR",
+            Web3WasmError::InvalidInput(_) => "INVALID_INPUT",
+            Web3WasmError::Program(_) => "PROGRAM_ERROR",
+        }
+    }
+
+    /// See [`RpcError::is_transient`], always `false` for other variants.
+    pub fn is_transient(&self) -> bool {
+        matches!(self, Web3WasmError::Rpc(err) if err.is_transient())
+    }
+
+    pub fn decode(err: impl Display) -> Self {
+        Web3WasmError::Decode(err.to_string())
+    }
+
+    pub fn invalid_input(err: impl Display) -> Self {
+        Web3WasmError::InvalidInput(err.to_string())
+    }
+
+    /// Wraps an error of `WasmClient`.
+    pub(crate) fn client(err: impl Display) -> Self {
+        Web3WasmError::Rpc(RpcError::from_client_message(err.to_string()))
+    }
+}
+
+impl From<serde_json::Error> for Web3WasmError {
+    fn from(err: serde_json::Error) -> Self {
+        Web3WasmError::Decode(format!("Invalid JSON: {err}"))
+    }
+}
+
+/// Borsh reports deserialization failures as `io::Error`.
+impl From<std::io::Error> for Web3WasmError {
+    fn from(err: std::io::Error) -> Self {
+        Web3WasmError::Decode(format!("Invalid account data: {err}"))
+    }
+}
+
+impl From<bincode::Error> for Web3WasmError {
+    fn from(err: bincode::Error) -> Self {
+        Web3WasmError::Decode(format!("Invalid bincode: {err}"))
+    }
+}
+
+impl From<ParsePubkeyError> for Web3WasmError {
+    fn from(err: ParsePubkeyError) -> Self {
+        Web3WasmError::InvalidInput(format!("Invalid pubkey: {err}"))
+    }
+}
+
+impl From<ProgramError> for Web3WasmError {
+    fn from(err: ProgramError) -> Self {
+        Web3WasmError::Program(err.to_string())
+    }
+}
+
+#[cfg(feature = "phantom")]
+impl From<crate::wallet::transaction::TransactionValueError> for Web3WasmError {
+    fn from(err: crate::wallet::transaction::TransactionValueError) -> Self {
+        Web3WasmError::InvalidInput(err.to_string())
+    }
+}
+
+#[cfg(feature = "phantom")]
+impl From<crate::wallet::transaction_v0::TransactionV0ValueError> for Web3WasmError {
+    fn from(err: crate::wallet::transaction_v0::TransactionV0ValueError) -> Self {
+        Web3WasmError::InvalidInput(err.to_string())
+    }
+}
+
+/// A JS `Error` whose `name` and `code` are `Web3WasmError::code()`.
+///
+/// `JsError` also converts from `Web3WasmError`, through wasm-bindgen's blanket impl,
+/// but only keeps the message.
+#[cfg(feature = "wasm_bindgen")]
+impl From<Web3WasmError> for wasm_bindgen::JsValue {
+    fn from(err: Web3WasmError) -> Self {
+        let js_error = js_sys::Error::new(&err.to_string());
+        js_error.set_name(err.code());
+        js_sys::Reflect::set(&js_error, &"code".into(), &err.code().into()).ok();
+        js_error.into()
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_codes_and_messages() {
+        let err = Web3WasmError::from(RpcError::JsonRpc {
+            code: -32602,
+            message: "Invalid param".to_owned(),
+        });
+        assert_eq!(err.code(), "RPC_ERROR");
+        assert_eq!(err.to_string(), "RPC error -32602: Invalid param");
+
+        let err = Web3WasmError::from("oops".parse::<solana_sdk::pubkey::Pubkey>().unwrap_err());
+        assert_eq!(err.code(), "INVALID_INPUT");
+
+        let err = Web3WasmError::from(serde_json::from_str::<u8>("x").unwrap_err());
+        assert_eq!(err.code(), "DECODE_ERROR");
+    }
+
+    #[test]
+    fn test_is_transient() {
+        let http = |status| RpcError::HttpStatus {
+            status,
+            retry_after: None,
+            body: String::new(),
+        };
+
+        assert!(Web3WasmError::from(http(429)).is_transient());
+        assert!(Web3WasmError::from(http(503)).is_transient());
+        assert!(!Web3WasmError::from(http(400)).is_transient());
+        assert!(Web3WasmError::from(RpcError::Timeout(Duration::from_secs(1))).is_transient());
+        assert!(Web3WasmError::client("error sending request for url").is_transient());
+        assert!(!Web3WasmError::client("Invalid param").is_transient());
+
+        let err = Web3WasmError::client(
+            "HTTP status client error (429 Too Many Requests) for url (https://rpc)",
+        );
+        assert!(matches!(
+            err,
+            Web3WasmError::Rpc(RpcError::HttpStatus { status: 429, .. })
+        ));
+        assert!(err.is_transient());
+        assert!(Web3WasmError::client("RPC response error -32005: Node is behind").is_transient());
+        assert!(!Web3WasmError::client("RPC response error -32602: Invalid param").is_transient());
+
+        // Digits and words of pubkeys and signatures in the message do not count.
+        assert!(!Web3WasmError::client(
+            "Invalid account 5o29Kzfm4291Y5zA503mKQM4D3ynetwork for instruction: connection"
+        )
+        .is_transient());
+        assert!(!Web3WasmError::NotFound("AccountNotFound".to_owned()).is_transient());
+    }
+}
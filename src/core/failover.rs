@@ -0,0 +1,330 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use async_trait::async_trait;
+use futures::future::join_all;
+use solana_client_wasm::{
+    utils::{rpc_config::RpcKeyedAccount, rpc_filter::TokenAccountsFilter},
+    WasmClient,
+};
+use solana_extra_wasm::account_decoder::parse_token::UiTokenAccount;
+use solana_sdk::{account::Account, clock::Slot, hash::Hash, pubkey::Pubkey};
+
+use crate::core::{
+    account::DataSlice,
+    client::{EndPoint, Web3WasmClient},
+    error::{Web3WasmError, Web3WasmResult},
+    program_accounts::ProgramAccountsConfig,
+    rpc::{RpcClient, RpcFuture},
+};
+
+/// Endpoints more than this many slots behind the highest reported slot are unhealthy.
+pub const DEFAULT_MAX_SLOT_LAG: u64 = 50;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EndpointHealth {
+    pub endpoint: EndPoint,
+    pub healthy: bool,
+    pub slot: Option<Slot>,
+    pub slot_lag: Option<u64>,
+}
+
+struct FailoverEndpoint<C> {
+    endpoint: EndPoint,
+    client: C,
+    healthy: AtomicBool,
+}
+
+/// Routes calls across an ordered list of endpoints.
+///
+/// Calls go to the active endpoint and move on to the next healthy one on
+/// transport errors or 429s. Earlier endpoints in the list are preferred.
+pub struct FailoverClient<C = WasmClient> {
+    endpoints: Vec<FailoverEndpoint<C>>,
+    active: AtomicUsize,
+    max_slot_lag: u64,
+}
+
+impl FailoverClient<WasmClient> {
+    pub fn new(endpoints: Vec<EndPoint>) -> Web3WasmResult<Self> {
+        Self::from_clients(
+            endpoints
+                .into_iter()
+                .map(|endpoint| {
+                    let client = <WasmClient as Web3WasmClient>::new(&endpoint);
+                    (endpoint, client)
+                })
+                .collect(),
+        )
+    }
+}
+
+impl<C: RpcClient> FailoverClient<C> {
+    pub fn from_clients(clients: Vec<(EndPoint, C)>) -> Web3WasmResult<Self> {
+        if clients.is_empty() {
+            return Err(Web3WasmError::InvalidInput(
+                "FailoverClient needs at least one endpoint".to_owned(),
+            ));
+        }
+
+        let endpoints = clients
+            .into_iter()
+            .map(|(endpoint, client)| FailoverEndpoint {
+                endpoint,
+                client,
+                healthy: AtomicBool::new(true),
+            })
+            .collect();
+
+        Ok(Self {
+            endpoints,
+            active: AtomicUsize::new(0),
+            max_slot_lag: DEFAULT_MAX_SLOT_LAG,
+        })
+    }
+
+    pub fn with_max_slot_lag(mut self, max_slot_lag: u64) -> Self {
+        self.max_slot_lag = max_slot_lag;
+        self
+    }
+
+    pub fn active_endpoint(&self) -> &EndPoint {
+        &self.endpoints[self.active.load(Ordering::Relaxed)].endpoint
+    }
+
+    pub fn active_client(&self) -> &C {
+        &self.endpoints[self.active.load(Ordering::Relaxed)].client
+    }
+
+    /// Checks `getHealth` and `getSlot` on every endpoint, then activates the
+    /// first healthy one that is within `max_slot_lag` of the highest slot.
+    pub async fn refresh_health(&self) -> Vec<EndpointHealth> {
+        let checks = self.endpoints.iter().map(|e| async move {
+            let health = e.client.get_health().await;
+            let slot = e.client.get_slot().await;
+            (health.is_ok(), slot.ok())
+        });
+        let results = join_all(checks).await;
+
+        let max_slot = results
+            .iter()
+            .filter_map(|(_, slot)| *slot)
+            .max()
+            .unwrap_or_default();
+
+        let report = self
+            .endpoints
+            .iter()
+            .zip(results)
+            .map(|(e, (is_ok, slot))| {
+                let slot_lag = slot.map(|slot| max_slot.saturating_sub(slot));
+                let healthy = is_ok && matches!(slot_lag, Some(lag) if lag <= self.max_slot_lag);
+
+                e.healthy.store(healthy, Ordering::Relaxed);
+
+                EndpointHealth {
+                    endpoint: e.endpoint.clone(),
+                    healthy,
+                    slot,
+                    slot_lag,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        if let Some(index) = report.iter().position(|e| e.healthy) {
+            self.active.store(index, Ordering::Relaxed);
+        }
+
+        report
+    }
+
+    /// Runs `f` against the active endpoint and fails over on transient errors.
+    ///
+    /// Healthy endpoints are tried first in list order, unhealthy ones last.
+    /// When every endpoint failed, the last endpoint's error is returned.
+    pub async fn call<'a, T, F>(&'a self, f: F) -> Web3WasmResult<T>
+    where
+        F: Fn(&'a C) -> RpcFuture<'a, T>,
+    {
+        let mut last_err = None;
+        for index in self.attempt_order() {
+            let e = &self.endpoints[index];
+            match f(&e.client).await {
+                Ok(value) => {
+                    e.healthy.store(true, Ordering::Relaxed);
+                    self.active.store(index, Ordering::Relaxed);
+                    return Ok(value);
+                }
+                Err(err) if err.is_transient() => {
+                    e.healthy.store(false, Ordering::Relaxed);
+                    last_err = Some(err);
+                }
+                Err(err) => return Err(err),
+            }
+        }
+
+        match last_err {
+            Some(err) => Err(err),
+            None => Err(Web3WasmError::InvalidInput(
+                "No endpoint available".to_owned(),
+            )),
+        }
+    }
+
+    fn attempt_order(&self) -> Vec<usize> {
+        let active = self.active.load(Ordering::Relaxed);
+        let (healthy, unhealthy): (Vec<_>, Vec<_>) = (0..self.endpoints.len())
+            .filter(|index| *index != active)
+            .partition(|index| self.endpoints[*index].healthy.load(Ordering::Relaxed));
+
+        std::iter::once(active)
+            .chain(healthy)
+            .chain(unhealthy)
+            .collect()
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<C: RpcClient> RpcClient for FailoverClient<C> {
+    async fn get_account(&self, pubkey: &Pubkey) -> Web3WasmResult<Account> {
+        self.call(|client| client.get_account(pubkey)).await
+    }
+
+    async fn get_account_data(&self, pubkey: &Pubkey) -> Web3WasmResult<Vec<u8>> {
+        self.call(|client| client.get_account_data(pubkey)).await
+    }
+
+    async fn get_multiple_accounts(
+        &self,
+        pubkeys: &[Pubkey],
+    ) -> Web3WasmResult<Vec<Option<Account>>> {
+        self.call(|client| client.get_multiple_accounts(pubkeys))
+            .await
+    }
+
+    async fn get_multiple_accounts_data_slice(
+        &self,
+        pubkeys: &[Pubkey],
+        slice: DataSlice,
+    ) -> Web3WasmResult<Vec<Option<Account>>> {
+        self.call(|client| client.get_multiple_accounts_data_slice(pubkeys, slice))
+            .await
+    }
+
+    async fn get_multiple_token_accounts(
+        &self,
+        pubkeys: &[Pubkey],
+    ) -> Web3WasmResult<Vec<Option<UiTokenAccount>>> {
+        self.call(|client| client.get_multiple_token_accounts(pubkeys))
+            .await
+    }
+
+    async fn get_token_accounts_by_owner(
+        &self,
+        owner: &Pubkey,
+        filter: &TokenAccountsFilter,
+    ) -> Web3WasmResult<Vec<RpcKeyedAccount>> {
+        self.call(|client| client.get_token_accounts_by_owner(owner, filter))
+            .await
+    }
+
+    async fn get_program_accounts(
+        &self,
+        program_id: &Pubkey,
+        config: &ProgramAccountsConfig,
+    ) -> Web3WasmResult<Vec<(Pubkey, Account)>> {
+        self.call(|client| client.get_program_accounts(program_id, config))
+            .await
+    }
+
+    async fn get_balance(&self, pubkey: &Pubkey) -> Web3WasmResult<u64> {
+        self.call(|client| client.get_balance(pubkey)).await
+    }
+
+    async fn get_slot(&self) -> Web3WasmResult<Slot> {
+        self.call(|client| client.get_slot()).await
+    }
+
+    async fn get_health(&self) -> Web3WasmResult<()> {
+        self.call(|client| client.get_health()).await
+    }
+
+    async fn get_genesis_hash(&self) -> Web3WasmResult<Hash> {
+        self.call(|client| client.get_genesis_hash()).await
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod test {
+    use serde_json::{json, Value};
+    use solana_sdk::pubkey::Pubkey;
+
+    use super::*;
+    use crate::tests::rpc_server::{StandInResponse, StandInRpcServer};
+
+    fn healthy_server(slot: u64, lamports: u64) -> impl Fn(&str, &Value) -> StandInResponse {
+        move |method, _| match method {
+            "getHealth" => StandInResponse::result(json!("ok")),
+            "getSlot" => StandInResponse::result(json!(slot)),
+            "getBalance" => StandInResponse::result(json!({
+                "context": { "slot": slot },
+                "value": lamports
+            })),
+            _ => StandInResponse::status(404),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failover_on_rate_limit() {
+        let limited = StandInRpcServer::start(|_, _| StandInResponse::status(429)).await;
+        let healthy = StandInRpcServer::start(healthy_server(100, 42)).await;
+
+        let client = FailoverClient::new(vec![
+            EndPoint::CustomUrl(limited.url()),
+            EndPoint::CustomUrl(healthy.url()),
+        ])
+        .unwrap();
+
+        let balance = client.get_balance(&Pubkey::new_unique()).await.unwrap();
+
+        assert_eq!(balance, 42);
+        assert_eq!(
+            client.active_endpoint(),
+            &EndPoint::CustomUrl(healthy.url())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failover_on_connection_refused() {
+        let healthy = StandInRpcServer::start(healthy_server(100, 7)).await;
+
+        let client = FailoverClient::new(vec![
+            EndPoint::CustomUrl(StandInRpcServer::unreachable_url()),
+            EndPoint::CustomUrl(healthy.url()),
+        ])
+        .unwrap();
+
+        assert_eq!(client.get_balance(&Pubkey::new_unique()).await.unwrap(), 7);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_health_skips_lagging_endpoint() {
+        let lagging = StandInRpcServer::start(healthy_server(100, 1)).await;
+        let synced = StandInRpcServer::start(healthy_server(1_000, 2)).await;
+
+        let client = FailoverClient::new(vec![
+            EndPoint::CustomUrl(lagging.url()),
+            EndPoint::CustomUrl(synced.url()),
+        ])
+        .unwrap();
+
+        let report = client.refresh_health().await;
+
+        assert!(!report[0].healthy);
+        assert_eq!(report[0].slot_lag, Some(900));
+        assert!(report[1].healthy);
+        assert_eq!(client.active_endpoint(), &EndPoint::CustomUrl(synced.url()));
+        assert_eq!(client.get_balance(&Pubkey::new_unique()).await.unwrap(), 2);
+    }
+}
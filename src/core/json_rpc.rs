@@ -0,0 +1,392 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use solana_client_wasm::utils::{rpc_config::RpcKeyedAccount, rpc_filter::TokenAccountsFilter};
+use solana_extra_wasm::account_decoder::{
+    parse_token::{TokenAccountType, UiTokenAccount},
+    UiAccount, UiAccountData,
+};
+use solana_sdk::{
+    account::Account, bs58, clock::Slot, commitment_config::CommitmentLevel, hash::Hash,
+    pubkey::Pubkey,
+};
+
+use crate::core::{
+    account::DataSlice,
+    client::ClientConfig,
+    error::{RpcError, Web3WasmError, Web3WasmResult},
+    program_accounts::{AccountFilter, ProgramAccountsConfig},
+    rpc::RpcClient,
+    transport::{HttpTransport, RpcTransport},
+};
+
+/// Typed JSON-RPC client over any `RpcTransport`.
+///
+/// `JsonRpcClient::new` talks HTTP and honours every `ClientConfig` option:
+/// commitment, per-request timeout, extra headers and bearer tokens.
+pub struct JsonRpcClient<T = HttpTransport> {
+    transport: T,
+    commitment: CommitmentLevel,
+    next_id: AtomicU64,
+}
+
+impl JsonRpcClient {
+    pub fn new(config: ClientConfig) -> Self {
+        Self::with_transport(HttpTransport::new(&config), config.commitment)
+    }
+}
+
+impl<T: RpcTransport> JsonRpcClient<T> {
+    pub fn with_transport(transport: T, commitment: CommitmentLevel) -> Self {
+        Self {
+            transport,
+            commitment,
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    pub fn commitment(&self) -> CommitmentLevel {
+        self.commitment
+    }
+
+    /// Sends a raw JSON-RPC request and returns its `result`.
+    pub async fn send(&self, method: &str, params: Value) -> Web3WasmResult<Value> {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": self.next_id.fetch_add(1, Ordering::Relaxed),
+            "method": method,
+            "params": params,
+        });
+
+        let response = self.transport.send(&request).await?;
+
+        if let Some(error) = response.get("error") {
+            return Err(RpcError::JsonRpc {
+                code: error["code"].as_i64().unwrap_or_default(),
+                message: error["message"].as_str().unwrap_or_default().to_owned(),
+            }
+            .into());
+        }
+
+        Ok(response["result"].clone())
+    }
+
+    fn commitment_config(&self) -> Value {
+        json!({ "commitment": self.commitment })
+    }
+
+    fn account_config(&self, encoding: &str) -> Value {
+        json!({ "encoding": encoding, "commitment": self.commitment })
+    }
+}
+
+fn decode_account(value: Value) -> Web3WasmResult<Option<Account>> {
+    if value.is_null() {
+        return Ok(None);
+    }
+
+    match serde_json::from_value::<UiAccount>(value)?.decode::<Account>() {
+        Some(account) => Ok(Some(account)),
+        None => Err(Web3WasmError::decode("Unable to decode account data")),
+    }
+}
+
+fn program_accounts_config(config: &ProgramAccountsConfig, commitment: CommitmentLevel) -> Value {
+    let filters = config
+        .filters
+        .iter()
+        .map(|filter| match filter {
+            AccountFilter::Memcmp { offset, bytes } => json!({
+                "memcmp": { "offset": offset, "bytes": bs58::encode(bytes).into_string() }
+            }),
+            AccountFilter::DataSize(size) => json!({ "dataSize": size }),
+        })
+        .collect::<Vec<_>>();
+
+    let mut value = json!({
+        "encoding": "base64",
+        "commitment": commitment,
+        "filters": filters,
+    });
+    if let Some(slice) = config.data_slice {
+        value["dataSlice"] = json!({ "offset": slice.offset, "length": slice.length });
+    }
+    value
+}
+
+fn parse_token_account(value: Value) -> Web3WasmResult<Option<UiTokenAccount>> {
+    if value.is_null() {
+        return Ok(None);
+    }
+
+    match serde_json::from_value::<UiAccount>(value)?.data {
+        UiAccountData::Json(parsed_account) => {
+            match serde_json::from_value(parsed_account.parsed) {
+                Ok(TokenAccountType::Account(ui_token_account)) => Ok(Some(ui_token_account)),
+                _ => Ok(None),
+            }
+        }
+        _ => Ok(None),
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<T: RpcTransport> RpcClient for JsonRpcClient<T> {
+    async fn get_account(&self, pubkey: &Pubkey) -> Web3WasmResult<Account> {
+        let result = self
+            .send(
+                "getAccountInfo",
+                json!([pubkey.to_string(), self.account_config("base64")]),
+            )
+            .await?;
+
+        match decode_account(result["value"].clone())? {
+            Some(account) => Ok(account),
+            None => Err(Web3WasmError::NotFound(format!(
+                "AccountNotFound: pubkey={}",
+                pubkey
+            ))),
+        }
+    }
+
+    async fn get_multiple_accounts(
+        &self,
+        pubkeys: &[Pubkey],
+    ) -> Web3WasmResult<Vec<Option<Account>>> {
+        let keys = pubkeys.iter().map(|e| e.to_string()).collect::<Vec<_>>();
+        let result = self
+            .send(
+                "getMultipleAccounts",
+                json!([keys, self.account_config("base64")]),
+            )
+            .await?;
+
+        serde_json::from_value::<Vec<Value>>(result["value"].clone())?
+            .into_iter()
+            .map(decode_account)
+            .collect()
+    }
+
+    async fn get_multiple_accounts_data_slice(
+        &self,
+        pubkeys: &[Pubkey],
+        slice: DataSlice,
+    ) -> Web3WasmResult<Vec<Option<Account>>> {
+        let keys = pubkeys.iter().map(|e| e.to_string()).collect::<Vec<_>>();
+        let mut config = self.account_config("base64");
+        config["dataSlice"] = json!({ "offset": slice.offset, "length": slice.length });
+        let result = self
+            .send("getMultipleAccounts", json!([keys, config]))
+            .await?;
+
+        serde_json::from_value::<Vec<Value>>(result["value"].clone())?
+            .into_iter()
+            .map(decode_account)
+            .collect()
+    }
+
+    async fn get_multiple_token_accounts(
+        &self,
+        pubkeys: &[Pubkey],
+    ) -> Web3WasmResult<Vec<Option<UiTokenAccount>>> {
+        let keys = pubkeys.iter().map(|e| e.to_string()).collect::<Vec<_>>();
+        let result = self
+            .send(
+                "getMultipleAccounts",
+                json!([keys, self.account_config("jsonParsed")]),
+            )
+            .await?;
+
+        serde_json::from_value::<Vec<Value>>(result["value"].clone())?
+            .into_iter()
+            .map(parse_token_account)
+            .collect()
+    }
+
+    async fn get_token_accounts_by_owner(
+        &self,
+        owner: &Pubkey,
+        filter: &TokenAccountsFilter,
+    ) -> Web3WasmResult<Vec<RpcKeyedAccount>> {
+        let filter = match filter {
+            TokenAccountsFilter::Mint(mint) => json!({ "mint": mint.to_string() }),
+            TokenAccountsFilter::ProgramId(program_id) => {
+                json!({ "programId": program_id.to_string() })
+            }
+        };
+        let result = self
+            .send(
+                "getTokenAccountsByOwner",
+                json!([owner.to_string(), filter, self.account_config("jsonParsed")]),
+            )
+            .await?;
+
+        Ok(serde_json::from_value(result["value"].clone())?)
+    }
+
+    async fn get_program_accounts(
+        &self,
+        program_id: &Pubkey,
+        config: &ProgramAccountsConfig,
+    ) -> Web3WasmResult<Vec<(Pubkey, Account)>> {
+        let result = self
+            .send(
+                "getProgramAccounts",
+                json!([
+                    program_id.to_string(),
+                    program_accounts_config(config, self.commitment)
+                ]),
+            )
+            .await?;
+
+        serde_json::from_value::<Vec<Value>>(result)?
+            .into_iter()
+            .map(|keyed_account| {
+                let pubkey = keyed_account["pubkey"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .parse::<Pubkey>()
+                    .map_err(Web3WasmError::decode)?;
+                match decode_account(keyed_account["account"].clone())? {
+                    Some(account) => Ok((pubkey, account)),
+                    None => Err(Web3WasmError::Decode(format!(
+                        "Missing account for {}",
+                        pubkey
+                    ))),
+                }
+            })
+            .collect()
+    }
+
+    async fn get_balance(&self, pubkey: &Pubkey) -> Web3WasmResult<u64> {
+        let result = self
+            .send(
+                "getBalance",
+                json!([pubkey.to_string(), self.commitment_config()]),
+            )
+            .await?;
+
+        match result["value"].as_u64() {
+            Some(lamports) => Ok(lamports),
+            None => Err(Web3WasmError::Decode(format!(
+                "Invalid getBalance response: {}",
+                result
+            ))),
+        }
+    }
+
+    async fn get_slot(&self) -> Web3WasmResult<Slot> {
+        let result = self
+            .send("getSlot", json!([self.commitment_config()]))
+            .await?;
+
+        match result.as_u64() {
+            Some(slot) => Ok(slot),
+            None => Err(Web3WasmError::Decode(format!(
+                "Invalid getSlot response: {}",
+                result
+            ))),
+        }
+    }
+
+    async fn get_health(&self) -> Web3WasmResult<()> {
+        let result = self.send("getHealth", json!([])).await?;
+
+        match result.as_str() {
+            Some("ok") => Ok(()),
+            _ => Err(Web3WasmError::Decode(format!(
+                "Invalid getHealth response: {}",
+                result
+            ))),
+        }
+    }
+
+    async fn get_genesis_hash(&self) -> Web3WasmResult<Hash> {
+        let result = self.send("getGenesisHash", json!([])).await?;
+
+        match result.as_str().map(str::parse::<Hash>) {
+            Some(Ok(hash)) => Ok(hash),
+            _ => Err(Web3WasmError::Decode(format!(
+                "Invalid getGenesisHash response: {}",
+                result
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod test {
+    use std::time::Duration;
+
+    use serde_json::json;
+    use solana_sdk::{commitment_config::CommitmentLevel, pubkey::Pubkey};
+
+    use super::*;
+    use crate::{
+        core::client::EndPoint,
+        tests::rpc_server::{StandInResponse, StandInRpcServer},
+    };
+
+    #[tokio::test]
+    async fn test_send_config_headers_and_commitment() {
+        let server = StandInRpcServer::start(|_, _| {
+            StandInResponse::result(json!({ "context": { "slot": 1 }, "value": 5 }))
+        })
+        .await;
+
+        let client = JsonRpcClient::new(
+            ClientConfig::builder(EndPoint::CustomUrl(server.url()))
+                .commitment(CommitmentLevel::Finalized)
+                .header("x-api-key", "secret")
+                .bearer_token("token")
+                .build(),
+        );
+
+        let balance = client.get_balance(&Pubkey::new_unique()).await.unwrap();
+        assert_eq!(balance, 5);
+
+        let request = &server.requests()[0];
+        assert_eq!(request.method(), "getBalance");
+        assert_eq!(request.header("x-api-key"), Some("secret"));
+        assert_eq!(request.header("authorization"), Some("Bearer token"));
+        assert_eq!(request.body["params"][1]["commitment"], "finalized");
+    }
+
+    #[tokio::test]
+    async fn test_send_rpc_error() {
+        let server =
+            StandInRpcServer::start(|_, _| StandInResponse::rpc_error(-32602, "Invalid param"))
+                .await;
+        let client = JsonRpcClient::new(ClientConfig::new(EndPoint::CustomUrl(server.url())));
+
+        let err = client.get_slot().await.unwrap_err();
+        assert_eq!(err.to_string(), "RPC error -32602: Invalid param");
+        assert!(!err.is_transient());
+    }
+
+    #[tokio::test]
+    async fn test_send_timeout() {
+        // Accepts connections but never answers.
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+
+        let client = JsonRpcClient::new(
+            ClientConfig::builder(EndPoint::CustomUrl(url))
+                .timeout(Duration::from_millis(200))
+                .build(),
+        );
+
+        let err = client.get_slot().await.unwrap_err();
+        assert_eq!(
+            err,
+            Web3WasmError::Rpc(RpcError::Timeout(Duration::from_millis(200)))
+        );
+    }
+}
@@ -0,0 +1,351 @@
+use std::collections::HashMap;
+
+use mpl_token_metadata::pda::find_metadata_account;
+use serde::{Deserialize, Serialize};
+use solana_extra_wasm::program::spl_token_2022;
+use solana_sdk::{account::Account, pubkey::Pubkey};
+
+use crate::core::{
+    account::{get_multiple_accounts_chunked, AccountResult},
+    error::Web3WasmResult,
+    rpc::RpcClient,
+    token_2022::{MintMetadataExtensions, Token2022Metadata},
+    token_metadata::{guess_asset_class, AssetClass, TokenMetadata},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MetadataSource {
+    /// `TokenMetadata` extension of a Token-2022 mint.
+    Token2022,
+    /// Metaplex metadata PDA.
+    Metaplex,
+}
+
+/// Token metadata, wherever the mint keeps it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResolvedMetadata {
+    pub mint: Pubkey,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub update_authority: Option<Pubkey>,
+    pub additional_metadata: Vec<(String, String)>,
+    pub source: MetadataSource,
+    /// The full Metaplex metadata, for `MetadataSource::Metaplex`.
+    pub metaplex: Option<TokenMetadata>,
+}
+
+impl ResolvedMetadata {
+    pub fn asset_class(&self, decimals: u8, supply: Option<u64>) -> AssetClass {
+        match &self.metaplex {
+            Some(metadata) => metadata.asset_class(decimals, supply),
+            None => guess_asset_class(decimals, supply),
+        }
+    }
+}
+
+impl From<Token2022Metadata> for ResolvedMetadata {
+    fn from(metadata: Token2022Metadata) -> Self {
+        Self {
+            mint: metadata.mint,
+            name: metadata.name,
+            symbol: metadata.symbol,
+            uri: metadata.uri,
+            update_authority: metadata.update_authority,
+            additional_metadata: metadata.additional_metadata,
+            source: MetadataSource::Token2022,
+            metaplex: None,
+        }
+    }
+}
+
+impl From<TokenMetadata> for ResolvedMetadata {
+    fn from(metadata: TokenMetadata) -> Self {
+        Self {
+            mint: metadata.mint,
+            name: metadata.name.clone(),
+            symbol: metadata.symbol.clone(),
+            uri: metadata.uri.clone(),
+            update_authority: Some(metadata.update_authority),
+            additional_metadata: vec![],
+            source: MetadataSource::Metaplex,
+            metaplex: Some(metadata),
+        }
+    }
+}
+
+fn decode_metaplex(account: Option<&Account>) -> AccountResult<ResolvedMetadata> {
+    match account {
+        Some(account) => TokenMetadata::from_account_data(&account.data)
+            .map(ResolvedMetadata::from)
+            .into(),
+        None => AccountResult::Missing,
+    }
+}
+
+/// Metadata account a `MetadataPointer` of `mint` leads to: a Metaplex PDA or another
+/// Token-2022 mint.
+///
+/// Only metadata of `mint` itself counts, held by an account of one of those programs.
+/// Anything else falls back to `metaplex`, a pointer can name any account.
+fn decode_pointed(
+    mint: &Pubkey,
+    account: &Account,
+    metaplex: AccountResult<ResolvedMetadata>,
+) -> AccountResult<ResolvedMetadata> {
+    let pointed = if account.owner == spl_token_2022::id() {
+        MintMetadataExtensions::unpack(&account.data)
+            .ok()
+            .and_then(|extensions| extensions.metadata)
+            .map(ResolvedMetadata::from)
+    } else if account.owner == mpl_token_metadata::id() {
+        TokenMetadata::from_account_data(&account.data)
+            .ok()
+            .map(ResolvedMetadata::from)
+    } else {
+        None
+    };
+
+    match pointed {
+        Some(metadata) if metadata.mint == *mint => AccountResult::Found(metadata),
+        _ => metaplex,
+    }
+}
+
+enum Resolution {
+    Done(AccountResult<ResolvedMetadata>),
+    /// Waiting on the account the `MetadataPointer` of a mint names, Metaplex result as
+    /// fallback.
+    Pointer(Pubkey, Pubkey, AccountResult<ResolvedMetadata>),
+}
+
+/// Metadata of every mint, in the order of `mints`.
+///
+/// A Token-2022 `TokenMetadata` extension wins, then the account its `MetadataPointer`
+/// names, then the Metaplex PDA. Mint and PDA accounts come in one batch, pointed
+/// accounts in a second one when needed.
+pub async fn resolve_multiple_metadata(
+    client: &impl RpcClient,
+    mints: &[Pubkey],
+) -> Web3WasmResult<Vec<AccountResult<ResolvedMetadata>>> {
+    let (_, metadata) = resolve_multiple_metadata_and_mints(client, mints).await?;
+    Ok(metadata)
+}
+
+/// [`resolve_multiple_metadata`], also handing back the mint accounts of its first batch.
+pub(crate) async fn resolve_multiple_metadata_and_mints(
+    client: &impl RpcClient,
+    mints: &[Pubkey],
+) -> Web3WasmResult<(Vec<Option<Account>>, Vec<AccountResult<ResolvedMetadata>>)> {
+    let keys = mints
+        .iter()
+        .flat_map(|mint| [*mint, find_metadata_account(mint).0])
+        .collect::<Vec<_>>();
+    let mut accounts = get_multiple_accounts_chunked(client, &keys).await?;
+
+    let (mint_accounts, resolutions): (Vec<_>, Vec<_>) = mints
+        .iter()
+        .zip(keys.chunks(2).zip(accounts.chunks_mut(2)))
+        .map(|(mint, (pair_keys, pair))| {
+            let metaplex = decode_metaplex(pair[1].as_ref());
+            // An unreadable mint falls back to Metaplex.
+            let extensions = pair[0]
+                .as_ref()
+                .filter(|account| account.owner == spl_token_2022::id())
+                .and_then(|account| MintMetadataExtensions::unpack(&account.data).ok())
+                .unwrap_or_default();
+            let pointer = extensions
+                .pointer
+                .and_then(|pointer| pointer.metadata_address);
+
+            let resolution = match (pointer, extensions.metadata) {
+                (None, Some(metadata)) => Resolution::Done(AccountResult::Found(metadata.into())),
+                (Some(address), Some(metadata)) if address == *mint => {
+                    Resolution::Done(AccountResult::Found(metadata.into()))
+                }
+                (Some(address), _) if address != *mint && address != pair_keys[1] => {
+                    Resolution::Pointer(*mint, address, metaplex)
+                }
+                _ => Resolution::Done(metaplex),
+            };
+            (pair[0].take(), resolution)
+        })
+        .unzip();
+
+    let pointed_keys = resolutions
+        .iter()
+        .filter_map(|resolution| match resolution {
+            Resolution::Pointer(_, address, _) => Some(*address),
+            Resolution::Done(_) => None,
+        })
+        .collect::<Vec<_>>();
+    let mut pointed_accounts = if pointed_keys.is_empty() {
+        vec![]
+    } else {
+        get_multiple_accounts_chunked(client, &pointed_keys).await?
+    }
+    .into_iter();
+
+    let metadata = resolutions
+        .into_iter()
+        .map(|resolution| match resolution {
+            Resolution::Done(result) => result,
+            Resolution::Pointer(mint, _, metaplex) => match pointed_accounts.next().flatten() {
+                Some(account) => decode_pointed(&mint, &account, metaplex),
+                None => metaplex,
+            },
+        })
+        .collect();
+    Ok((mint_accounts, metadata))
+}
+
+/// [`resolve_multiple_metadata`] keyed by mint address.
+pub async fn resolve_mint_metadata_map(
+    client: &impl RpcClient,
+    mints: &[Pubkey],
+) -> Web3WasmResult<HashMap<String, AccountResult<ResolvedMetadata>>> {
+    let metadata = resolve_multiple_metadata(client, mints).await?;
+    Ok(mints
+        .iter()
+        .map(|mint| mint.to_string())
+        .zip(metadata)
+        .collect())
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod test {
+    use serde_json::Value;
+
+    use super::*;
+    use crate::{
+        core::{
+            client::{ClientConfig, EndPoint},
+            json_rpc::JsonRpcClient,
+            token_2022::test::{metadata_pointer, mint_with_extensions, token_metadata},
+        },
+        tests::rpc_server::{account_json, StandInResponse, StandInRpcServer},
+    };
+
+    #[tokio::test]
+    async fn test_resolve_multiple_metadata() {
+        let fixture: Value =
+            serde_json::from_str(include_str!("../../tests/fixtures/metadata.json")).unwrap();
+        let usdc_metadata = fixture[0]["response"]["result"]["value"][0].clone();
+        let usdc: Pubkey = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v"
+            .parse()
+            .unwrap();
+
+        // Metadata in the mint itself.
+        let embedded = Pubkey::new_unique();
+        // Metadata in another Token-2022 mint.
+        let pointing = Pubkey::new_unique();
+        let pointed = Pubkey::new_unique();
+        // Pointer to an account that does not exist, with no Metaplex PDA either.
+        let dangling = Pubkey::new_unique();
+
+        let token_2022 = |data: Vec<u8>| account_json(&spl_token_2022::id(), &data);
+        let accounts = HashMap::from([
+            (find_metadata_account(&usdc).0, usdc_metadata),
+            (
+                embedded,
+                token_2022(mint_with_extensions(&[
+                    metadata_pointer(&embedded),
+                    token_metadata(&embedded, "Paxos Gold", "PAXG"),
+                ])),
+            ),
+            (
+                pointing,
+                token_2022(mint_with_extensions(&[metadata_pointer(&pointed)])),
+            ),
+            (
+                pointed,
+                token_2022(mint_with_extensions(&[token_metadata(
+                    &pointing, "Pointed", "PTD",
+                )])),
+            ),
+            (
+                dangling,
+                token_2022(mint_with_extensions(&[metadata_pointer(
+                    &Pubkey::new_unique(),
+                )])),
+            ),
+        ]);
+        let server = StandInRpcServer::start(move |_, params| {
+            StandInResponse::accounts(params, |key| accounts.get(key).cloned())
+        })
+        .await;
+        let client = JsonRpcClient::new(ClientConfig::new(EndPoint::CustomUrl(server.url())));
+
+        let results = resolve_multiple_metadata(&client, &[usdc, embedded, pointing, dangling])
+            .await
+            .unwrap();
+
+        let usdc_metadata = results[0].as_found().unwrap();
+        assert_eq!(usdc_metadata.symbol, "USDC");
+        assert_eq!(usdc_metadata.source, MetadataSource::Metaplex);
+        assert_eq!(usdc_metadata.asset_class(6, None), AssetClass::Fungible);
+
+        let embedded_metadata = results[1].as_found().unwrap();
+        assert_eq!(embedded_metadata.symbol, "PAXG");
+        assert_eq!(embedded_metadata.source, MetadataSource::Token2022);
+        assert_eq!(embedded_metadata.asset_class(6, None), AssetClass::Fungible);
+
+        assert_eq!(results[2].as_found().unwrap().name, "Pointed");
+        assert_eq!(results[3], AccountResult::Missing);
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_pointer_to_other_mint() {
+        let fixture: Value =
+            serde_json::from_str(include_str!("../../tests/fixtures/metadata.json")).unwrap();
+        let usdc: Pubkey = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v"
+            .parse()
+            .unwrap();
+        let usdc_metadata = find_metadata_account(&usdc).0;
+        let other = Pubkey::new_unique();
+
+        // Pointers to the Metaplex metadata of USDC, to the metadata of another Token-2022
+        // mint, and to an account of some other program holding a copy of it.
+        let to_metaplex = Pubkey::new_unique();
+        let to_token_2022 = Pubkey::new_unique();
+        let to_copy = Pubkey::new_unique();
+        let copy = Pubkey::new_unique();
+        let other_data = mint_with_extensions(&[token_metadata(&other, "Other", "OTH")]);
+
+        let token_2022 = |data: Vec<u8>| account_json(&spl_token_2022::id(), &data);
+        let accounts = HashMap::from([
+            (
+                usdc_metadata,
+                fixture[0]["response"]["result"]["value"][0].clone(),
+            ),
+            (
+                to_metaplex,
+                token_2022(mint_with_extensions(&[metadata_pointer(&usdc_metadata)])),
+            ),
+            (
+                to_token_2022,
+                token_2022(mint_with_extensions(&[metadata_pointer(&other)])),
+            ),
+            (
+                to_copy,
+                token_2022(mint_with_extensions(&[metadata_pointer(&copy)])),
+            ),
+            (other, token_2022(other_data.clone())),
+            (copy, account_json(&Pubkey::new_unique(), &other_data)),
+        ]);
+        let server = StandInRpcServer::start(move |_, params| {
+            StandInResponse::accounts(params, |key| accounts.get(key).cloned())
+        })
+        .await;
+        let client = JsonRpcClient::new(ClientConfig::new(EndPoint::CustomUrl(server.url())));
+
+        let results = resolve_multiple_metadata(&client, &[to_metaplex, to_token_2022, to_copy])
+            .await
+            .unwrap();
+
+        // None of them has a Metaplex PDA to fall back to.
+        assert_eq!(results, vec![AccountResult::Missing; 3]);
+    }
+}
@@ -1,17 +1,39 @@
 use std::collections::HashMap;
 
-use anyhow::bail;
 use mpl_token_metadata::{
     pda::find_metadata_account,
-    state::{Data, Metadata},
+    state::{Data, Metadata, EDITION, PREFIX},
 };
-use solana_client_wasm::WasmClient;
-use solana_sdk::{borsh::try_from_slice_unchecked, pubkey::Pubkey};
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
 
+use crate::core::{
+    account::{decode_borsh, get_multiple_accounts_chunked, AccountResult},
+    error::{Web3WasmError, Web3WasmResult},
+    rpc::RpcClient,
+    token_metadata::BorshReader,
+};
+
+/// Strips the NUL padding Metaplex stores fixed-size strings with.
+fn trim_metadata(mut metadata: Metadata) -> Metadata {
+    metadata.data = Data {
+        name: metadata.data.name.trim_matches(char::from(0)).to_owned(),
+        symbol: metadata.data.symbol.trim_matches(char::from(0)).to_owned(),
+        uri: metadata.data.uri.trim_matches(char::from(0)).to_owned(),
+        seller_fee_basis_points: metadata.data.seller_fee_basis_points,
+        creators: metadata.data.creators,
+    };
+    metadata
+}
+
+/// Metadata of every mint, in the order of `mints`.
+///
+/// `AccountResult::Missing` means the mint has no metadata account. A broken
+/// metadata account only fails its own entry, as `AccountResult::DecodeError`.
 pub async fn get_multiple_token_metadata(
-    client: &WasmClient,
+    client: &impl RpcClient,
     mints: &[Pubkey],
-) -> anyhow::Result<Vec<Metadata>> {
+) -> Web3WasmResult<Vec<AccountResult<Metadata>>> {
     let metadata_keys = mints
         .iter()
         .map(|mint| {
@@ -20,85 +42,479 @@ pub async fn get_multiple_token_metadata(
         })
         .collect::<Vec<_>>();
 
-    let metadata_accounts = client.get_multiple_accounts(&metadata_keys).await?;
-    let mut errors = vec![];
+    let metadata_accounts = get_multiple_accounts_chunked(client, &metadata_keys).await?;
     let result = metadata_accounts
         .into_iter()
-        .flatten()
-        .map(|account| try_from_slice_unchecked::<Metadata>(&account.data))
-        .filter_map(|r| r.map_err(|e| errors.push(e)).ok());
-
-    let result = result
-        .map(|mut metadata| {
-            metadata.data = Data {
-                name: metadata.data.name.trim_matches(char::from(0)).to_owned(),
-                symbol: metadata.data.symbol.trim_matches(char::from(0)).to_owned(),
-                uri: metadata.data.uri.trim_matches(char::from(0)).to_owned(),
-                seller_fee_basis_points: metadata.data.seller_fee_basis_points,
-                creators: metadata.data.creators,
-            };
-            metadata
+        .map(|account| match account {
+            Some(account) => decode_borsh::<Metadata>(&account.data).map(trim_metadata),
+            None => AccountResult::Missing,
         })
         .collect::<Vec<_>>();
 
-    if !errors.is_empty() {
-        bail!("errors: {:?}", errors)
-    }
-
     Ok(result)
 }
 
+/// [`get_multiple_token_metadata`] keyed by mint address, with an entry for every mint.
 pub async fn get_mint_metadata_map(
-    client: &WasmClient,
+    client: &impl RpcClient,
+    mints: &[Pubkey],
+) -> Web3WasmResult<HashMap<String, AccountResult<Metadata>>> {
+    let metadata = get_multiple_token_metadata(client, mints).await?;
+    Ok(mints
+        .iter()
+        .map(|mint| mint.to_string())
+        .zip(metadata)
+        .collect())
+}
+
+// Edition -------------------------------------
+
+/// `Key::EditionV1`.
+const EDITION_KEY: u8 = 1;
+/// `Key::MasterEditionV1`, same prefix as V2.
+const MASTER_EDITION_V1_KEY: u8 = 2;
+/// `Key::MasterEditionV2`.
+const MASTER_EDITION_V2_KEY: u8 = 6;
+/// `Key::EditionMarker`.
+const EDITION_MARKER_KEY: u8 = 7;
+/// Editions tracked by one edition marker account.
+pub const EDITION_MARKER_BIT_SIZE: u64 = 248;
+
+/// Edition PDA of `mint`, holding either its master edition or its print edition.
+pub fn find_edition_account(mint: &Pubkey) -> (Pubkey, u8) {
+    let program_id = mpl_token_metadata::id();
+    Pubkey::find_program_address(
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            mint.as_ref(),
+            EDITION.as_bytes(),
+        ],
+        &program_id,
+    )
+}
+
+/// Marker PDA recording which prints of `parent_mint` around `edition` were minted.
+pub fn find_edition_marker_account(parent_mint: &Pubkey, edition: u64) -> (Pubkey, u8) {
+    let program_id = mpl_token_metadata::id();
+    Pubkey::find_program_address(
+        &[
+            PREFIX.as_bytes(),
+            program_id.as_ref(),
+            parent_mint.as_ref(),
+            EDITION.as_bytes(),
+            (edition / EDITION_MARKER_BIT_SIZE).to_string().as_bytes(),
+        ],
+        &program_id,
+    )
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MasterEditionInfo {
+    pub address: Pubkey,
+    /// Prints minted so far.
+    pub supply: u64,
+    /// `None` for unlimited prints, `Some(0)` for a one of one.
+    pub max_supply: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrintEditionInfo {
+    pub address: Pubkey,
+    /// Master edition account the print was minted from.
+    pub parent: Pubkey,
+    pub edition: u64,
+}
+
+/// Decoded edition PDA of a mint.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EditionInfo {
+    Master(MasterEditionInfo),
+    Print(PrintEditionInfo),
+}
+
+impl EditionInfo {
+    pub fn is_original(&self) -> bool {
+        matches!(self, EditionInfo::Master(_))
+    }
+
+    pub fn decode(address: Pubkey, data: &[u8]) -> Web3WasmResult<Self> {
+        let mut reader = BorshReader::new(data);
+        match reader.u8()? {
+            MASTER_EDITION_V1_KEY | MASTER_EDITION_V2_KEY => {
+                Ok(EditionInfo::Master(MasterEditionInfo {
+                    address,
+                    supply: reader.u64()?,
+                    max_supply: reader.option(BorshReader::u64)?,
+                }))
+            }
+            EDITION_KEY => Ok(EditionInfo::Print(PrintEditionInfo {
+                address,
+                parent: reader.pubkey()?,
+                edition: reader.u64()?,
+            })),
+            key => Err(Web3WasmError::Decode(format!(
+                "Not an edition account: key {key}"
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EditionMarkerInfo {
+    pub address: Pubkey,
+    pub edition: u64,
+    /// Whether the marker has the bit of `edition` set.
+    pub is_minted: bool,
+}
+
+impl EditionMarkerInfo {
+    pub fn decode(address: Pubkey, edition: u64, data: &[u8]) -> Web3WasmResult<Self> {
+        let mut reader = BorshReader::new(data);
+        let key = reader.u8()?;
+        if key != EDITION_MARKER_KEY {
+            return Err(Web3WasmError::Decode(format!(
+                "Not an edition marker account: key {key}"
+            )));
+        }
+        let ledger = reader.bytes(31)?;
+
+        // Same bit layout as `EditionMarker::get_index_and_mask`.
+        let offset = edition % EDITION_MARKER_BIT_SIZE;
+        let mask = 1u8 << (7 - offset % 8);
+        Ok(Self {
+            address,
+            edition,
+            is_minted: ledger[(offset / 8) as usize] & mask != 0,
+        })
+    }
+}
+
+/// A print resolved against its master edition and edition marker.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrintEditionDetails {
+    pub print: PrintEditionInfo,
+    pub master: MasterEditionInfo,
+    pub marker: AccountResult<EditionMarkerInfo>,
+}
+
+/// Edition PDA of every mint, in the order of `mints`.
+///
+/// `AccountResult::Missing` means the mint is not an edition at all, e.g. a fungible token.
+pub async fn get_multiple_editions(
+    client: &impl RpcClient,
     mints: &[Pubkey],
-) -> anyhow::Result<HashMap<String, Metadata>> {
-    let mut mint_metadata_map = HashMap::new();
-    get_multiple_token_metadata(client, mints)
+) -> Web3WasmResult<Vec<AccountResult<EditionInfo>>> {
+    let edition_keys = mints
+        .iter()
+        .map(|mint| find_edition_account(mint).0)
+        .collect::<Vec<_>>();
+
+    let edition_accounts = get_multiple_accounts_chunked(client, &edition_keys).await?;
+    Ok(edition_keys
+        .into_iter()
+        .zip(edition_accounts)
+        .map(|(address, account)| match account {
+            Some(account) => EditionInfo::decode(address, &account.data).into(),
+            None => AccountResult::Missing,
+        })
+        .collect())
+}
+
+/// Resolves `(print_mint, parent_mint)` pairs, in order, with two batched round trips.
+///
+/// An entry is a `DecodeError` when the print mint is not a print of `parent_mint`.
+pub async fn get_multiple_print_editions(
+    client: &impl RpcClient,
+    prints: &[(Pubkey, Pubkey)],
+) -> Web3WasmResult<Vec<AccountResult<PrintEditionDetails>>> {
+    let mints = prints
+        .iter()
+        .flat_map(|(print_mint, parent_mint)| [*print_mint, *parent_mint])
+        .collect::<Vec<_>>();
+    let editions = get_multiple_editions(client, &mints).await?;
+
+    let resolved = editions
+        .chunks(2)
+        .map(|pair| match (&pair[0], &pair[1]) {
+            (
+                AccountResult::Found(EditionInfo::Print(print)),
+                AccountResult::Found(EditionInfo::Master(master)),
+            ) => {
+                if print.parent == master.address {
+                    AccountResult::Found((print.clone(), master.clone()))
+                } else {
+                    AccountResult::DecodeError(format!(
+                        "Print {} is not minted from {}",
+                        print.address, master.address
+                    ))
+                }
+            }
+            (AccountResult::Found(_), AccountResult::Found(_)) => {
+                AccountResult::DecodeError("Expected a print and its master edition".to_owned())
+            }
+            (AccountResult::DecodeError(err), _) | (_, AccountResult::DecodeError(err)) => {
+                AccountResult::DecodeError(err.clone())
+            }
+            _ => AccountResult::Missing,
+        })
+        .collect::<Vec<_>>();
+
+    let marker_keys = prints
+        .iter()
+        .zip(&resolved)
+        .filter_map(|((_, parent_mint), result)| {
+            let (print, _) = result.as_found()?;
+            Some(find_edition_marker_account(parent_mint, print.edition).0)
+        })
+        .collect::<Vec<_>>();
+    let mut marker_accounts = get_multiple_accounts_chunked(client, &marker_keys)
         .await?
         .into_iter()
-        .for_each(|metadata| {
-            mint_metadata_map.insert(metadata.mint.to_string(), metadata);
-        });
+        .zip(marker_keys);
 
-    Ok(mint_metadata_map)
+    Ok(resolved
+        .into_iter()
+        .map(|result| {
+            result.map(|(print, master)| {
+                let (account, address) = marker_accounts
+                    .next()
+                    .expect("one marker per resolved print");
+                let marker = match account {
+                    Some(account) => {
+                        EditionMarkerInfo::decode(address, print.edition, &account.data).into()
+                    }
+                    None => AccountResult::Missing,
+                };
+                PrintEditionDetails {
+                    print,
+                    master,
+                    marker,
+                }
+            })
+        })
+        .collect())
 }
 
 #[cfg(test)]
 #[cfg(not(target_arch = "wasm32"))]
 mod test {
-    use std::str::FromStr;
+    use std::{collections::HashMap, str::FromStr};
 
-    use solana_client_wasm::WasmClient;
+    use serde_json::{json, Value};
     use solana_sdk::pubkey::Pubkey;
 
-    use crate::core::{
-        client::EndPoint,
-        metaplex::{get_mint_metadata_map, get_multiple_token_metadata},
+    use super::*;
+    use crate::{
+        core::{
+            account::AccountResult,
+            client::{ClientConfig, EndPoint},
+            json_rpc::JsonRpcClient,
+        },
+        tests::{
+            fixtures::fixture_client,
+            rpc_server::{account_json, StandInResponse, StandInRpcServer},
+        },
     };
 
     #[tokio::test]
     async fn test_get_multiple_token_metadata() {
-        let client = WasmClient::new(EndPoint::Mainnet.to_string().as_ref());
-        let token_metadata_info = get_multiple_token_metadata(
-            &client,
-            &[Pubkey::from_str("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v").unwrap()],
-        )
-        .await
-        .unwrap();
+        let client = fixture_client("metadata");
+        let mint = Pubkey::from_str("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v").unwrap();
+        let token_metadata_info = get_multiple_token_metadata(&client, &[mint]).await.unwrap();
+
+        let metadata = token_metadata_info[0].as_found().unwrap();
+        assert_eq!(metadata.mint, mint);
+        assert_eq!(metadata.data.name, "USD Coin");
+        assert_eq!(metadata.data.symbol, "USDC");
+    }
+
+    #[tokio::test]
+    async fn test_get_mint_metadata_map() {
+        let client = fixture_client("metadata");
+        let mint_address = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+        let token_metadata_map =
+            get_mint_metadata_map(&client, &[Pubkey::from_str(mint_address).unwrap()])
+                .await
+                .unwrap();
 
-        println!("token_metadata_info: {:#?}", token_metadata_info);
+        assert_eq!(
+            token_metadata_map[mint_address]
+                .as_found()
+                .unwrap()
+                .data
+                .symbol,
+            "USDC"
+        );
     }
 
     #[tokio::test]
-    async fn test_get_multiple_token_symbol() {
-        let client = WasmClient::new(EndPoint::Mainnet.to_string().as_ref());
-        let token_metadata_map = get_mint_metadata_map(
+    async fn test_get_mint_metadata_map_partial() {
+        let fixture: Value =
+            serde_json::from_str(include_str!("../../tests/fixtures/metadata.json")).unwrap();
+        let usdc_metadata = fixture[0]["response"]["result"]["value"][0].clone();
+        let mut broken_metadata = usdc_metadata.clone();
+        broken_metadata["data"][0] = json!(base64::encode([4, 1, 2]));
+
+        let server = StandInRpcServer::start(move |_, _| {
+            StandInResponse::value(json!([usdc_metadata, Value::Null, broken_metadata]))
+        })
+        .await;
+        let client = JsonRpcClient::new(ClientConfig::new(EndPoint::CustomUrl(server.url())));
+        let mints = [
+            Pubkey::from_str("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v").unwrap(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        ];
+
+        let token_metadata_map = get_mint_metadata_map(&client, &mints).await.unwrap();
+
+        assert_eq!(token_metadata_map.len(), 3);
+        assert_eq!(
+            token_metadata_map[&mints[0].to_string()]
+                .as_found()
+                .unwrap()
+                .data
+                .symbol,
+            "USDC"
+        );
+        assert!(matches!(
+            token_metadata_map[&mints[1].to_string()],
+            AccountResult::Missing
+        ));
+        assert!(matches!(
+            token_metadata_map[&mints[2].to_string()],
+            AccountResult::DecodeError(_)
+        ));
+    }
+
+    fn master_edition_data(supply: u64, max_supply: Option<u64>) -> Vec<u8> {
+        let mut data = vec![MASTER_EDITION_V2_KEY];
+        data.extend(supply.to_le_bytes());
+        match max_supply {
+            Some(max_supply) => {
+                data.push(1);
+                data.extend(max_supply.to_le_bytes());
+            }
+            None => data.push(0),
+        }
+        data
+    }
+
+    fn print_edition_data(parent: &Pubkey, edition: u64) -> Vec<u8> {
+        let mut data = vec![EDITION_KEY];
+        data.extend(parent.to_bytes());
+        data.extend(edition.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn test_decode_editions() {
+        let address = Pubkey::new_unique();
+        assert_eq!(
+            EditionInfo::decode(address, &master_edition_data(3, Some(10))).unwrap(),
+            EditionInfo::Master(MasterEditionInfo {
+                address,
+                supply: 3,
+                max_supply: Some(10)
+            })
+        );
+
+        let parent = Pubkey::new_unique();
+        let print = EditionInfo::decode(address, &print_edition_data(&parent, 251)).unwrap();
+        assert!(!print.is_original());
+        assert_eq!(
+            print,
+            EditionInfo::Print(PrintEditionInfo {
+                address,
+                parent,
+                edition: 251
+            })
+        );
+
+        // Edition 251 is bit 3 of the second marker.
+        let mut marker = vec![EDITION_MARKER_KEY, 0b0001_0000];
+        marker.extend([0; 30]);
+        assert!(
+            EditionMarkerInfo::decode(address, 251, &marker)
+                .unwrap()
+                .is_minted
+        );
+        assert!(
+            !EditionMarkerInfo::decode(address, 252, &marker)
+                .unwrap()
+                .is_minted
+        );
+        assert!(EditionInfo::decode(address, &marker).is_err());
+
+        assert_ne!(
+            find_edition_marker_account(&parent, 247),
+            find_edition_marker_account(&parent, 248)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_multiple_print_editions() {
+        let parent_mint = Pubkey::new_unique();
+        let print_mint = Pubkey::new_unique();
+        let other_print_mint = Pubkey::new_unique();
+        let fungible_mint = Pubkey::new_unique();
+        let (master_address, _) = find_edition_account(&parent_mint);
+        let (marker_address, _) = find_edition_marker_account(&parent_mint, 5);
+        let mut marker = vec![EDITION_MARKER_KEY, 0b0000_0100];
+        marker.extend([0; 30]);
+
+        let accounts = HashMap::from([
+            (master_address, master_edition_data(6, None)),
+            (
+                find_edition_account(&print_mint).0,
+                print_edition_data(&master_address, 5),
+            ),
+            (
+                find_edition_account(&other_print_mint).0,
+                print_edition_data(&Pubkey::new_unique(), 1),
+            ),
+            (marker_address, marker),
+        ]);
+        let server = StandInRpcServer::start(move |_, params| {
+            StandInResponse::accounts(params, |key| {
+                let data = accounts.get(key)?;
+                Some(account_json(&mpl_token_metadata::id(), data))
+            })
+        })
+        .await;
+        let client = JsonRpcClient::new(ClientConfig::new(EndPoint::CustomUrl(server.url())));
+
+        let editions = get_multiple_editions(&client, &[parent_mint, fungible_mint])
+            .await
+            .unwrap();
+        assert!(editions[0].as_found().unwrap().is_original());
+        assert_eq!(editions[1], AccountResult::Missing);
+
+        let prints = get_multiple_print_editions(
             &client,
-            &[Pubkey::from_str("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v").unwrap()],
+            &[
+                (print_mint, parent_mint),
+                (other_print_mint, parent_mint),
+                (fungible_mint, parent_mint),
+            ],
         )
         .await
         .unwrap();
 
-        println!("token_metadata_map: {:#?}", token_metadata_map);
+        let details = prints[0].as_found().unwrap();
+        assert_eq!(details.print.edition, 5);
+        assert_eq!(details.master.supply, 6);
+        assert_eq!(details.master.max_supply, None);
+        assert_eq!(
+            details.marker,
+            AccountResult::Found(EditionMarkerInfo {
+                address: marker_address,
+                edition: 5,
+                is_minted: true
+            })
+        );
+        assert!(matches!(prints[1], AccountResult::DecodeError(_)));
+        assert_eq!(prints[2], AccountResult::Missing);
     }
 }
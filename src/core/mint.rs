@@ -1,44 +1,185 @@
-use serde_json::Value;
+use serde::{Deserialize, Serialize};
+use solana_extra_wasm::program::{
+    spl_token,
+    spl_token::state::{Account as TokenAccount, Mint},
+    spl_token_2022,
+};
+use solana_sdk::{account::Account, program_pack::Pack, pubkey::Pubkey};
 
-use solana_client_wasm::WasmClient;
-use solana_extra_wasm::program::spl_token::state::Mint;
-use solana_sdk::{program_pack::Pack, pubkey::Pubkey};
+use crate::core::{
+    account::{get_and_unpack_account_data, get_multiple_accounts_chunked, AccountResult},
+    client::ClusterId,
+    error::{Web3WasmError, Web3WasmResult},
+    rpc::RpcClient,
+    token_2022::{account_type, unpack_mint_extensions, AccountType, MintExtension},
+    token_registry::TokenRegistry,
+};
 
-pub async fn get_mint_info(
-    client: &WasmClient,
-    mint_pubkey: &Pubkey,
-) -> Result<Mint, anyhow::Error> {
-    let account = client.get_account(mint_pubkey).await?;
-    Ok(Mint::unpack(&account.data)?)
+/// Fails on Token-2022 mints with extensions, see [`get_mint_details`].
+pub async fn get_mint_info(client: &impl RpcClient, mint_pubkey: &Pubkey) -> Web3WasmResult<Mint> {
+    get_and_unpack_account_data::<Mint>(client, mint_pubkey).await
 }
 
-// TODO: Support token that not exists via Raydium
-pub fn get_logo_by_mint_address(mint_address: &str) -> String {
-    let token_data = r#"{
-    "EsM2FadUJFzVxtzdekL7VThiYSfGrCNcvVqzNLiW4J8a": "https://76ejs4snpuheojjadpqx3uvjvwu6ubbo6zyoni7czu3v3o3tei7q.arweave.net/_4iZck19DkclIBvhfdKpranqBC72cOaj4s03XbtzIj8?ext=jpg"
-  }"#;
-
-    let raydium_url = format!("https://img.raydium.io/icon/{mint_address}.png");
-    let token_value: Result<Value, serde_json::Error> = serde_json::from_str(token_data);
-    match token_value {
-        Ok(value) => {
-            let url = &value[mint_address];
-            if url.is_null() {
-                raydium_url
-            } else {
-                url.as_str().unwrap_or(raydium_url.as_str()).to_owned()
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TokenProgram {
+    SplToken,
+    SplToken2022,
+}
+
+impl TokenProgram {
+    pub fn from_owner(owner: &Pubkey) -> Option<Self> {
+        if *owner == spl_token::id() {
+            Some(Self::SplToken)
+        } else if *owner == spl_token_2022::id() {
+            Some(Self::SplToken2022)
+        } else {
+            None
+        }
+    }
+
+    pub fn id(&self) -> Pubkey {
+        match self {
+            Self::SplToken => spl_token::id(),
+            Self::SplToken2022 => spl_token_2022::id(),
+        }
+    }
+}
+
+/// A mint of either token program, with its Token-2022 extensions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MintInfo {
+    pub address: Pubkey,
+    pub program: TokenProgram,
+    pub decimals: u8,
+    pub supply: u64,
+    pub mint_authority: Option<Pubkey>,
+    pub freeze_authority: Option<Pubkey>,
+    /// Always empty for `TokenProgram::SplToken`.
+    pub extensions: Vec<MintExtension>,
+}
+
+impl MintInfo {
+    /// Both programs share the base `Mint` layout, Token-2022 keeps extensions after it.
+    pub fn unpack(address: Pubkey, account: &Account) -> Web3WasmResult<Self> {
+        let program = TokenProgram::from_owner(&account.owner).ok_or_else(|| {
+            Web3WasmError::Decode(format!(
+                "{address} is owned by {}, not a token program",
+                account.owner
+            ))
+        })?;
+        let data = &account.data;
+        let is_mint = match program {
+            TokenProgram::SplToken => data.len() == Mint::LEN,
+            // With extensions, mints are longer than a token account and typed as a mint.
+            TokenProgram::SplToken2022 => {
+                data.len() == Mint::LEN
+                    || (data.len() > TokenAccount::LEN
+                        && account_type(data) == Some(AccountType::Mint))
             }
+        };
+        if !is_mint {
+            return Err(Web3WasmError::Decode(format!(
+                "{address} is not a mint, its data is {} bytes",
+                data.len()
+            )));
         }
+        let mint = Mint::unpack(&data[..Mint::LEN]).map_err(Web3WasmError::decode)?;
+        let extensions = match program {
+            TokenProgram::SplToken => vec![],
+            TokenProgram::SplToken2022 => unpack_mint_extensions(&account.data)?,
+        };
+
+        Ok(Self {
+            address,
+            program,
+            decimals: mint.decimals,
+            supply: mint.supply,
+            mint_authority: mint.mint_authority.into(),
+            freeze_authority: mint.freeze_authority.into(),
+            extensions,
+        })
+    }
 
-        Err(_) => raydium_url,
+    /// [`MintInfo::unpack`] of a fetched account, `AccountResult::Missing` when there is none.
+    pub fn from_fetched(address: Pubkey, account: Option<&Account>) -> AccountResult<Self> {
+        match account {
+            Some(account) => Self::unpack(address, account).into(),
+            None => AccountResult::Missing,
+        }
     }
+
+    /// The extension stored as `extension_type`, e.g. `PERMANENT_DELEGATE_EXTENSION`.
+    pub fn extension(&self, extension_type: u16) -> Option<&MintExtension> {
+        self.extensions
+            .iter()
+            .find(|extension| extension.extension_type() == extension_type)
+    }
+}
+
+/// [`MintInfo`] of a mint owned by either token program.
+pub async fn get_mint_details(
+    client: &impl RpcClient,
+    mint_pubkey: &Pubkey,
+) -> Web3WasmResult<MintInfo> {
+    let account = client.get_account(mint_pubkey).await?;
+    MintInfo::unpack(*mint_pubkey, &account)
+}
+
+/// Batch version of [`get_mint_details`], one entry per key.
+///
+/// Mints of both token programs come in the same `getMultipleAccounts` requests,
+/// one per [`crate::core::account::MAX_MULTIPLE_ACCOUNTS`] keys.
+pub async fn get_multiple_mint_details(
+    client: &impl RpcClient,
+    mint_pubkeys: &[Pubkey],
+) -> Web3WasmResult<Vec<AccountResult<MintInfo>>> {
+    let maybe_accounts = get_multiple_accounts_chunked(client, mint_pubkeys).await?;
+    Ok(mint_pubkeys
+        .iter()
+        .zip(maybe_accounts)
+        .map(|(mint_pubkey, account)| MintInfo::from_fetched(*mint_pubkey, account.as_ref()))
+        .collect())
+}
+
+/// Decimals of every mint, in the order of `mint_pubkeys`, for transfer builders.
+///
+/// Fails on the first mint that is missing or not a mint rather than guessing.
+pub async fn get_multiple_mint_decimals(
+    client: &impl RpcClient,
+    mint_pubkeys: &[Pubkey],
+) -> Web3WasmResult<Vec<u8>> {
+    get_multiple_mint_details(client, mint_pubkeys)
+        .await?
+        .into_iter()
+        .zip(mint_pubkeys)
+        .map(|(info, mint_pubkey)| Ok(info.into_result(mint_pubkey)?.decimals))
+        .collect()
+}
+
+/// Logo of a mainnet token from the built-in [`TokenRegistry`], Raydium's guess otherwise.
+pub fn get_logo_by_mint_address(mint_address: &str) -> String {
+    TokenRegistry::built_in_ref().logo(ClusterId::Mainnet, mint_address)
 }
 
 #[cfg(test)]
 #[cfg(not(target_arch = "wasm32"))]
 mod test {
-    use crate::core::client::{EndPoint, Web3WasmClient};
-    use std::str::FromStr;
+    use crate::{
+        core::{
+            client::{ClientConfig, EndPoint, Web3WasmClient},
+            json_rpc::JsonRpcClient,
+            token_2022::{
+                test::{metadata_pointer, mint_with_extensions, transfer_fee_config},
+                PERMANENT_DELEGATE_EXTENSION,
+            },
+        },
+        tests::{
+            fixtures::fixture_client,
+            rpc_server::{account_json, StandInResponse, StandInRpcServer},
+        },
+    };
+    use solana_client_wasm::WasmClient;
+    use std::{collections::HashMap, str::FromStr};
 
     use super::*;
 
@@ -52,7 +193,7 @@ mod test {
 
     #[tokio::test]
     async fn test_get_mint_info() {
-        let client = Web3WasmClient::new_mainnet();
+        let client = fixture_client("mint");
         let mint_info = get_mint_info(
             &client,
             &Pubkey::from_str("So11111111111111111111111111111111111111112").unwrap(),
@@ -63,10 +204,145 @@ mod test {
         assert_eq!(mint_info.decimals, 9)
     }
 
+    #[test]
+    fn test_mint_info_unpack_length() {
+        let address = Pubkey::new_unique();
+        let account = |owner: Pubkey, data: Vec<u8>| Account {
+            lamports: 1,
+            data,
+            owner,
+            executable: false,
+            rent_epoch: 0,
+        };
+        let mut mint = vec![0; Mint::LEN];
+        mint[44] = 6;
+        mint[45] = 1;
+        let mut padded = mint.clone();
+        padded.resize(TokenAccount::LEN, 0);
+
+        assert!(MintInfo::unpack(address, &account(spl_token::id(), mint.clone())).is_ok());
+        assert!(MintInfo::unpack(address, &account(spl_token_2022::id(), mint)).is_ok());
+        assert!(MintInfo::unpack(
+            address,
+            &account(spl_token_2022::id(), mint_with_extensions(&[]))
+        )
+        .is_ok());
+
+        // A token account is long enough, but is not a mint.
+        assert!(MintInfo::unpack(address, &account(spl_token::id(), padded.clone())).is_err());
+        assert!(MintInfo::unpack(address, &account(spl_token_2022::id(), padded.clone())).is_err());
+        // Token-2022 account with extensions.
+        padded.push(2);
+        assert!(MintInfo::unpack(address, &account(spl_token_2022::id(), padded)).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_mint_details() {
+        let client = fixture_client("mint");
+        let wsol = get_mint_details(
+            &client,
+            &Pubkey::from_str("So11111111111111111111111111111111111111112").unwrap(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(wsol.program, TokenProgram::SplToken);
+        assert_eq!(wsol.decimals, 9);
+        assert_eq!(wsol.mint_authority, None);
+        assert!(wsol.extensions.is_empty());
+
+        let mint = Pubkey::new_unique();
+        let delegate = Pubkey::new_unique();
+        let mut data = mint_with_extensions(&[
+            transfer_fee_config(50, 1_000),
+            (PERMANENT_DELEGATE_EXTENSION, delegate.to_bytes().to_vec()),
+            metadata_pointer(&mint),
+        ]);
+        // Mint authority and supply.
+        data[..4].copy_from_slice(&1u32.to_le_bytes());
+        data[4..36].copy_from_slice(&delegate.to_bytes());
+        data[36..44].copy_from_slice(&1_000_000u64.to_le_bytes());
+        let server = StandInRpcServer::start(move |_, _| {
+            StandInResponse::value(account_json(&spl_token_2022::id(), &data))
+        })
+        .await;
+        let client = JsonRpcClient::new(ClientConfig::new(EndPoint::CustomUrl(server.url())));
+
+        let info = get_mint_details(&client, &mint).await.unwrap();
+        assert_eq!(info.program, TokenProgram::SplToken2022);
+        assert_eq!(info.decimals, 6);
+        assert_eq!(info.supply, 1_000_000);
+        assert_eq!(info.mint_authority, Some(delegate));
+        assert_eq!(info.freeze_authority, None);
+        assert_eq!(info.extensions.len(), 3);
+        assert_eq!(
+            info.extension(PERMANENT_DELEGATE_EXTENSION),
+            Some(&MintExtension::PermanentDelegate(Some(delegate)))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_multiple_mint_details() {
+        let wsol = Pubkey::from_str("So11111111111111111111111111111111111111112").unwrap();
+        let token_2022 = Pubkey::new_unique();
+        let missing = Pubkey::new_unique();
+        let not_a_mint = Pubkey::new_unique();
+
+        let account = |data: Vec<u8>, owner: Pubkey| account_json(&owner, &data);
+        let mut wsol_data = vec![0; Mint::LEN];
+        wsol_data[44] = 9;
+        wsol_data[45] = 1;
+        let accounts = HashMap::from([
+            (wsol, account(wsol_data, spl_token::id())),
+            (
+                token_2022,
+                account(
+                    mint_with_extensions(&[transfer_fee_config(50, 1_000)]),
+                    spl_token_2022::id(),
+                ),
+            ),
+            (
+                not_a_mint,
+                account(vec![1; Mint::LEN], Pubkey::new_unique()),
+            ),
+        ]);
+        let server = StandInRpcServer::start(move |method, params| {
+            assert_eq!(method, "getMultipleAccounts");
+            StandInResponse::accounts(params, |key| accounts.get(key).cloned())
+        })
+        .await;
+        let client = JsonRpcClient::new(ClientConfig::new(EndPoint::CustomUrl(server.url())));
+
+        let infos = get_multiple_mint_details(&client, &[wsol, token_2022, missing, not_a_mint])
+            .await
+            .unwrap();
+        assert_eq!(server.requests().len(), 1);
+        assert_eq!(infos[0].as_found().unwrap().program, TokenProgram::SplToken);
+        let token_2022_info = infos[1].as_found().unwrap();
+        assert_eq!(token_2022_info.program, TokenProgram::SplToken2022);
+        assert_eq!(token_2022_info.decimals, 6);
+        assert_eq!(token_2022_info.extensions.len(), 1);
+        assert_eq!(infos[2], AccountResult::Missing);
+        assert!(matches!(infos[3], AccountResult::DecodeError(_)));
+
+        assert_eq!(
+            get_multiple_mint_decimals(&client, &[wsol, token_2022])
+                .await
+                .unwrap(),
+            vec![9, 6]
+        );
+        assert_eq!(
+            get_multiple_mint_decimals(&client, &[wsol, missing])
+                .await
+                .unwrap_err()
+                .code(),
+            "NOT_FOUND"
+        );
+    }
+
     #[tokio::test]
     #[ignore]
     async fn test_custom_rpc() {
-        let client = Web3WasmClient::new(&EndPoint::CustomUrl(
+        let client: WasmClient = Web3WasmClient::new(&EndPoint::CustomUrl(
             "https://rpc.ankr.com/solana".to_owned(),
         ));
         let mint_info = get_mint_info(
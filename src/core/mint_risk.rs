@@ -0,0 +1,342 @@
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::core::{
+    account::AccountResult,
+    error::Web3WasmResult,
+    metadata_resolver::{resolve_multiple_metadata_and_mints, ResolvedMetadata},
+    metaplex::find_edition_account,
+    mint::MintInfo,
+    rpc::RpcClient,
+    token_2022::{AccountState, MintExtension},
+};
+
+/// Ordered, so the worst finding of a report is its `max`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum RiskSeverity {
+    Low,
+    Medium,
+    High,
+}
+
+/// Something the mint or its metadata allows that can hurt a holder.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MintRisk {
+    /// More supply can be minted.
+    MintAuthority(Pubkey),
+    /// Token accounts can be frozen.
+    FreezeAuthority(Pubkey),
+    /// Tokens can be transferred or burned from any account.
+    PermanentDelegate(Pubkey),
+    /// Every transfer runs this program, which can make it fail.
+    TransferHook(Pubkey),
+    /// Tokens can never leave the account they were minted to.
+    NonTransferable,
+    /// New token accounts start frozen.
+    DefaultFrozen,
+    TransferFee {
+        basis_points: u16,
+        maximum_fee: u64,
+    },
+    /// Name, symbol and URI can still change.
+    MutableMetadata {
+        update_authority: Option<Pubkey>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RiskFinding {
+    pub risk: MintRisk,
+    pub severity: RiskSeverity,
+}
+
+/// Where findings turn high.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MintRiskConfig {
+    /// Transfer fees at or above this are `High`, lower ones `Low`.
+    pub high_transfer_fee_basis_points: u16,
+}
+
+impl Default for MintRiskConfig {
+    fn default() -> Self {
+        Self {
+            high_transfer_fee_basis_points: 500,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MintRiskReport {
+    pub mint: Pubkey,
+    /// Worst first.
+    pub findings: Vec<RiskFinding>,
+}
+
+impl MintRiskReport {
+    /// Findings of `mint`, with `metadata` from [`resolve_multiple_metadata`] when known.
+    ///
+    /// A mint authority held by the Metaplex edition PDA is not reported, no one can mint
+    /// more of an NFT through it. Transfer fees are checked against the newer fee, which
+    /// is either in force or about to be.
+    pub fn analyze(
+        mint: &MintInfo,
+        metadata: Option<&ResolvedMetadata>,
+        config: &MintRiskConfig,
+    ) -> Self {
+        let mut findings = vec![];
+        let mut push = |risk, severity| findings.push(RiskFinding { risk, severity });
+
+        if let Some(authority) = mint.mint_authority {
+            if authority != find_edition_account(&mint.address).0 {
+                push(MintRisk::MintAuthority(authority), RiskSeverity::Medium);
+            }
+        }
+        if let Some(authority) = mint.freeze_authority {
+            push(MintRisk::FreezeAuthority(authority), RiskSeverity::Medium);
+        }
+
+        for extension in &mint.extensions {
+            match extension {
+                MintExtension::PermanentDelegate(Some(delegate)) => {
+                    push(MintRisk::PermanentDelegate(*delegate), RiskSeverity::High)
+                }
+                MintExtension::TransferHook(hook) => {
+                    if let Some(program_id) = hook.program_id {
+                        push(MintRisk::TransferHook(program_id), RiskSeverity::Medium)
+                    }
+                }
+                MintExtension::NonTransferable => {
+                    push(MintRisk::NonTransferable, RiskSeverity::High)
+                }
+                MintExtension::DefaultAccountState(AccountState::Frozen) => {
+                    push(MintRisk::DefaultFrozen, RiskSeverity::High)
+                }
+                MintExtension::TransferFeeConfig(fees) => {
+                    let fee = &fees.newer_transfer_fee;
+                    if fee.transfer_fee_basis_points > 0 {
+                        let severity = if fee.transfer_fee_basis_points
+                            >= config.high_transfer_fee_basis_points
+                        {
+                            RiskSeverity::High
+                        } else {
+                            RiskSeverity::Low
+                        };
+                        push(
+                            MintRisk::TransferFee {
+                                basis_points: fee.transfer_fee_basis_points,
+                                maximum_fee: fee.maximum_fee,
+                            },
+                            severity,
+                        )
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(metadata) = metadata {
+            // Token-2022 metadata is mutable for as long as it has an update authority.
+            let is_mutable = match &metadata.metaplex {
+                Some(metaplex) => metaplex.is_mutable,
+                None => metadata.update_authority.is_some(),
+            };
+            if is_mutable {
+                push(
+                    MintRisk::MutableMetadata {
+                        update_authority: metadata.update_authority,
+                    },
+                    RiskSeverity::Low,
+                );
+            }
+        }
+
+        findings.sort_by(|a, b| b.severity.cmp(&a.severity));
+        Self {
+            mint: mint.address,
+            findings,
+        }
+    }
+
+    pub fn max_severity(&self) -> Option<RiskSeverity> {
+        self.findings.iter().map(|finding| finding.severity).max()
+    }
+
+    pub fn is_clean(&self) -> bool {
+        self.findings.is_empty()
+    }
+}
+
+/// Risk reports of many mints, each mint account fetched once along with its metadata.
+pub async fn get_multiple_mint_risk_reports(
+    client: &impl RpcClient,
+    mint_pubkeys: &[Pubkey],
+    config: &MintRiskConfig,
+) -> Web3WasmResult<Vec<AccountResult<MintRiskReport>>> {
+    let (mint_accounts, metadata) =
+        resolve_multiple_metadata_and_mints(client, mint_pubkeys).await?;
+    Ok(mint_pubkeys
+        .iter()
+        .zip(mint_accounts)
+        .zip(metadata)
+        .map(|((mint_pubkey, account), metadata)| {
+            MintInfo::from_fetched(*mint_pubkey, account.as_ref())
+                .map(|mint| MintRiskReport::analyze(&mint, metadata.as_found(), config))
+        })
+        .collect())
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod test {
+    use std::collections::HashMap;
+
+    use solana_extra_wasm::program::spl_token_2022;
+
+    use super::*;
+    use crate::{
+        core::{
+            client::{ClientConfig, EndPoint},
+            json_rpc::JsonRpcClient,
+            metadata_resolver::MetadataSource,
+            mint::TokenProgram,
+            token_2022::{
+                test::{
+                    metadata_pointer, mint_with_extensions, token_metadata, transfer_fee_config,
+                },
+                TransferFee, TransferFeeConfig, TransferHook,
+            },
+        },
+        tests::rpc_server::{account_json, StandInResponse, StandInRpcServer},
+    };
+
+    fn mint(extensions: Vec<MintExtension>) -> MintInfo {
+        MintInfo {
+            address: Pubkey::new_unique(),
+            program: TokenProgram::SplToken2022,
+            decimals: 6,
+            supply: 1_000_000,
+            mint_authority: None,
+            freeze_authority: None,
+            extensions,
+        }
+    }
+
+    fn transfer_fee(basis_points: u16) -> MintExtension {
+        let fee = TransferFee {
+            epoch: 0,
+            maximum_fee: u64::MAX,
+            transfer_fee_basis_points: basis_points,
+        };
+        MintExtension::TransferFeeConfig(TransferFeeConfig {
+            transfer_fee_config_authority: None,
+            withdraw_withheld_authority: None,
+            withheld_amount: 0,
+            older_transfer_fee: fee,
+            newer_transfer_fee: fee,
+        })
+    }
+
+    #[test]
+    fn test_analyze_mint_risk() {
+        let config = MintRiskConfig::default();
+        assert!(MintRiskReport::analyze(&mint(vec![]), None, &config).is_clean());
+
+        let delegate = Pubkey::new_unique();
+        let hook = Pubkey::new_unique();
+        let mut rug = mint(vec![
+            transfer_fee(1_000),
+            MintExtension::PermanentDelegate(Some(delegate)),
+            MintExtension::TransferHook(TransferHook {
+                authority: None,
+                program_id: Some(hook),
+            }),
+            MintExtension::DefaultAccountState(AccountState::Frozen),
+            MintExtension::MintCloseAuthority(None),
+        ]);
+        rug.mint_authority = Some(delegate);
+        rug.freeze_authority = Some(delegate);
+        let metadata = ResolvedMetadata {
+            mint: rug.address,
+            name: "Rug".to_owned(),
+            symbol: "RUG".to_owned(),
+            uri: String::new(),
+            update_authority: Some(delegate),
+            additional_metadata: vec![],
+            source: MetadataSource::Token2022,
+            metaplex: None,
+        };
+
+        let report = MintRiskReport::analyze(&rug, Some(&metadata), &config);
+        assert_eq!(report.max_severity(), Some(RiskSeverity::High));
+        assert_eq!(report.findings.len(), 7);
+        assert_eq!(report.findings[0].severity, RiskSeverity::High);
+        assert_eq!(report.findings[6].severity, RiskSeverity::Low);
+        let risks = report
+            .findings
+            .into_iter()
+            .map(|finding| finding.risk)
+            .collect::<Vec<_>>();
+        assert!(risks.contains(&MintRisk::PermanentDelegate(delegate)));
+        assert!(risks.contains(&MintRisk::TransferHook(hook)));
+        assert!(risks.contains(&MintRisk::DefaultFrozen));
+        assert!(risks.contains(&MintRisk::TransferFee {
+            basis_points: 1_000,
+            maximum_fee: u64::MAX,
+        }));
+        assert!(risks.contains(&MintRisk::MutableMetadata {
+            update_authority: Some(delegate),
+        }));
+
+        // A low fee, and an NFT minted through its master edition.
+        let mut nft = mint(vec![transfer_fee(100)]);
+        nft.mint_authority = Some(find_edition_account(&nft.address).0);
+        let report = MintRiskReport::analyze(&nft, None, &config);
+        assert_eq!(report.max_severity(), Some(RiskSeverity::Low));
+        assert_eq!(report.findings.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_get_multiple_mint_risk_reports() {
+        let mint = Pubkey::new_unique();
+        let missing = Pubkey::new_unique();
+        let data = mint_with_extensions(&[
+            transfer_fee_config(50, 1_000),
+            metadata_pointer(&mint),
+            token_metadata(&mint, "Paxos Gold", "PAXG"),
+        ]);
+        let accounts = HashMap::from([(mint, account_json(&spl_token_2022::id(), &data))]);
+        let server = StandInRpcServer::start(move |_, params| {
+            StandInResponse::accounts(params, |key| accounts.get(key).cloned())
+        })
+        .await;
+        let client = JsonRpcClient::new(ClientConfig::new(EndPoint::CustomUrl(server.url())));
+
+        let reports =
+            get_multiple_mint_risk_reports(&client, &[mint, missing], &MintRiskConfig::default())
+                .await
+                .unwrap();
+
+        // Mints and metadata PDAs in a single batch, no mint fetched twice.
+        assert_eq!(server.requests().len(), 1);
+        let risks = reports[0]
+            .as_found()
+            .unwrap()
+            .findings
+            .iter()
+            .map(|finding| finding.risk.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            risks,
+            vec![
+                MintRisk::TransferFee {
+                    basis_points: 100,
+                    maximum_fee: 1_000,
+                },
+                MintRisk::MutableMetadata {
+                    update_authority: Some(Pubkey::new_from_array([7; 32])),
+                },
+            ]
+        );
+        assert_eq!(reports[1], AccountResult::Missing);
+    }
+}
@@ -1,7 +1,24 @@
 pub mod account;
+pub mod anchor;
 pub mod buffer;
+pub mod cache;
 pub mod client;
+pub mod error;
+pub mod failover;
 pub mod hash;
+pub mod json_rpc;
+pub mod metadata_resolver;
 pub mod metaplex;
 pub mod mint;
+pub mod mint_risk;
+pub mod program_accounts;
 pub mod pubkey;
+#[cfg(feature = "pubsub")]
+pub mod pubsub;
+pub mod retry;
+pub mod royalty;
+pub mod rpc;
+pub mod token_2022;
+pub mod token_metadata;
+pub mod token_registry;
+pub mod transport;
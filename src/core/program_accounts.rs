@@ -0,0 +1,289 @@
+use borsh::BorshDeserialize;
+use solana_sdk::{
+    program_pack::{IsInitialized, Pack},
+    pubkey::Pubkey,
+};
+
+use crate::core::{
+    account::{decode_borsh, decode_pack, AccountResult, DataSlice},
+    anchor::{account_discriminator, decode_anchor_account, DISCRIMINATOR_LEN},
+    error::{Web3WasmError, Web3WasmResult},
+    rpc::RpcClient,
+};
+
+/// `getProgramAccounts` filter, all filters of a request must match.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccountFilter {
+    /// Account data contains `bytes` at `offset`.
+    Memcmp { offset: usize, bytes: Vec<u8> },
+    /// Account data is exactly this many bytes long.
+    DataSize(u64),
+}
+
+impl AccountFilter {
+    pub fn memcmp(offset: usize, bytes: impl Into<Vec<u8>>) -> Self {
+        AccountFilter::Memcmp {
+            offset,
+            bytes: bytes.into(),
+        }
+    }
+
+    pub fn pubkey(offset: usize, pubkey: &Pubkey) -> Self {
+        Self::memcmp(offset, pubkey.to_bytes())
+    }
+
+    /// Little-endian, as Borsh and `Pack` store integers.
+    pub fn u64(offset: usize, value: u64) -> Self {
+        Self::memcmp(offset, value.to_le_bytes())
+    }
+
+    /// Anchor accounts named `account_name`.
+    pub fn discriminator(account_name: &str) -> Self {
+        Self::memcmp(0, account_discriminator(account_name))
+    }
+
+    pub fn data_size(size: u64) -> Self {
+        AccountFilter::DataSize(size)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProgramAccountsConfig {
+    pub filters: Vec<AccountFilter>,
+    pub data_slice: Option<DataSlice>,
+}
+
+impl ProgramAccountsConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn filter(mut self, filter: AccountFilter) -> Self {
+        self.filters.push(filter);
+        self
+    }
+
+    pub fn data_slice(mut self, offset: usize, length: usize) -> Self {
+        self.data_slice = Some(DataSlice::new(offset, length));
+        self
+    }
+}
+
+/// Every account of `program_id` matching `config`, Borsh-decoded.
+///
+/// Accounts that fail to decode are kept as `AccountResult::DecodeError`.
+pub async fn get_and_deserialize_program_accounts<T: BorshDeserialize>(
+    client: &impl RpcClient,
+    program_id: &Pubkey,
+    config: &ProgramAccountsConfig,
+) -> Web3WasmResult<Vec<(Pubkey, AccountResult<T>)>> {
+    let accounts = client.get_program_accounts(program_id, config).await?;
+    Ok(accounts
+        .into_iter()
+        .map(|(pubkey, account)| (pubkey, decode_borsh(&account.data)))
+        .collect())
+}
+
+/// Every account of `program_id` matching `config`, unpacked as a `Pack` type.
+///
+/// Accounts that are not exactly `T::LEN` bytes or not initialized are kept as
+/// `AccountResult::DecodeError`; filter on `AccountFilter::data_size(T::LEN)` to skip them.
+pub async fn get_and_unpack_program_accounts<T: Pack + IsInitialized>(
+    client: &impl RpcClient,
+    program_id: &Pubkey,
+    config: &ProgramAccountsConfig,
+) -> Web3WasmResult<Vec<(Pubkey, AccountResult<T>)>> {
+    let accounts = client.get_program_accounts(program_id, config).await?;
+    Ok(accounts
+        .into_iter()
+        .map(|(pubkey, account)| (pubkey, decode_pack(&account.data)))
+        .collect())
+}
+
+/// Every `account_name` Anchor account of `program_id` matching `config`.
+///
+/// The discriminator filter is added for you, so `config` only needs the field filters.
+/// Offsets of those filters count the 8 discriminator bytes.
+///
+/// The discriminator is checked on the returned data, so a data slice has to start at 0
+/// and keep at least those 8 bytes; other slices are rejected as `InvalidInput`.
+pub async fn get_and_deserialize_anchor_program_accounts<T: BorshDeserialize>(
+    client: &impl RpcClient,
+    program_id: &Pubkey,
+    account_name: &str,
+    config: &ProgramAccountsConfig,
+) -> Web3WasmResult<Vec<(Pubkey, AccountResult<T>)>> {
+    if let Some(slice) = config.data_slice {
+        if slice.offset != 0 || slice.length < DISCRIMINATOR_LEN {
+            return Err(Web3WasmError::InvalidInput(format!(
+                "Data slice of {} bytes at offset {} drops the {account_name} discriminator",
+                slice.length, slice.offset
+            )));
+        }
+    }
+
+    let config = config
+        .clone()
+        .filter(AccountFilter::discriminator(account_name));
+    let accounts = client.get_program_accounts(program_id, &config).await?;
+    Ok(accounts
+        .into_iter()
+        .map(|(pubkey, account)| {
+            let result = decode_anchor_account(&account, account_name, program_id).into();
+            (pubkey, result)
+        })
+        .collect())
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod test {
+    use borsh::BorshSerialize;
+    use serde_json::json;
+    use solana_extra_wasm::program::{spl_token, spl_token::state::Mint};
+    use solana_sdk::program_option::COption;
+
+    use super::*;
+    use crate::{
+        core::{
+            client::{ClientConfig, EndPoint},
+            json_rpc::JsonRpcClient,
+        },
+        tests::rpc_server::{account_json, StandInResponse, StandInRpcServer},
+    };
+
+    #[derive(Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+    struct Position {
+        owner: Pubkey,
+        amount: u64,
+    }
+
+    #[tokio::test]
+    async fn test_get_and_deserialize_program_accounts() {
+        let program_id = Pubkey::new_from_array([9; 32]);
+        let owner = Pubkey::new_from_array([1; 32]);
+        let found = Pubkey::new_from_array([2; 32]);
+        let broken = Pubkey::new_from_array([3; 32]);
+        let data = Position { owner, amount: 5 }.try_to_vec().unwrap();
+
+        let server = StandInRpcServer::start(move |method, _| {
+            assert_eq!(method, "getProgramAccounts");
+            let account = |data: &[u8]| account_json(&program_id, data);
+            StandInResponse::result(json!([
+                { "pubkey": found.to_string(), "account": account(&data) },
+                { "pubkey": broken.to_string(), "account": account(&[1]) },
+            ]))
+        })
+        .await;
+        let client = JsonRpcClient::new(ClientConfig::new(EndPoint::CustomUrl(server.url())));
+        let config = ProgramAccountsConfig::new()
+            .filter(AccountFilter::data_size(40))
+            .filter(AccountFilter::pubkey(0, &owner))
+            .filter(AccountFilter::u64(32, 5))
+            .data_slice(0, 40);
+
+        let results =
+            get_and_deserialize_program_accounts::<Position>(&client, &program_id, &config)
+                .await
+                .unwrap();
+
+        assert_eq!(
+            results[0],
+            (found, AccountResult::Found(Position { owner, amount: 5 }))
+        );
+        assert_eq!(results[1].0, broken);
+        assert!(matches!(results[1].1, AccountResult::DecodeError(_)));
+
+        let params = &server.requests()[0].body["params"];
+        assert_eq!(params[0], program_id.to_string());
+        assert_eq!(
+            params[1]["filters"],
+            json!([
+                { "dataSize": 40 },
+                { "memcmp": { "offset": 0, "bytes": owner.to_string() } },
+                { "memcmp": { "offset": 32, "bytes": "qWN536U3Lb" } },
+            ])
+        );
+        assert_eq!(params[1]["dataSlice"], json!({ "offset": 0, "length": 40 }));
+        assert_eq!(params[1]["encoding"], "base64");
+    }
+
+    #[tokio::test]
+    async fn test_get_and_unpack_program_accounts() {
+        let mint = Mint {
+            mint_authority: COption::None,
+            supply: 42,
+            decimals: 6,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        };
+        let mut data = vec![0; Mint::LEN];
+        mint.pack_into_slice(&mut data);
+        let found = Pubkey::new_unique();
+        let uninitialized = Pubkey::new_unique();
+
+        let server = StandInRpcServer::start(move |_, _| {
+            let account = |data: &[u8]| account_json(&spl_token::id(), data);
+            StandInResponse::result(json!([
+                { "pubkey": found.to_string(), "account": account(&data) },
+                { "pubkey": uninitialized.to_string(), "account": account(&[0; Mint::LEN]) },
+            ]))
+        })
+        .await;
+        let client = JsonRpcClient::new(ClientConfig::new(EndPoint::CustomUrl(server.url())));
+        let config =
+            ProgramAccountsConfig::new().filter(AccountFilter::data_size(Mint::LEN as u64));
+
+        let results = get_and_unpack_program_accounts::<Mint>(&client, &spl_token::id(), &config)
+            .await
+            .unwrap();
+
+        assert_eq!(results[0], (found, AccountResult::Found(mint)));
+        assert_eq!(results[1].0, uninitialized);
+        assert!(matches!(results[1].1, AccountResult::DecodeError(_)));
+    }
+
+    #[tokio::test]
+    async fn test_get_and_deserialize_anchor_program_accounts() {
+        let server = StandInRpcServer::start(|_, _| StandInResponse::result(json!([]))).await;
+        let client = JsonRpcClient::new(ClientConfig::new(EndPoint::CustomUrl(server.url())));
+
+        let results = get_and_deserialize_anchor_program_accounts::<Position>(
+            &client,
+            &Pubkey::new_unique(),
+            "Position",
+            &ProgramAccountsConfig::new(),
+        )
+        .await
+        .unwrap();
+
+        assert!(results.is_empty());
+        let filters = &server.requests()[0].body["params"][1]["filters"];
+        assert_eq!(
+            filters[0]["memcmp"]["bytes"],
+            solana_sdk::bs58::encode(account_discriminator("Position")).into_string()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_anchor_program_accounts_reject_sliced_discriminator() {
+        let server = StandInRpcServer::start(|_, _| StandInResponse::result(json!([]))).await;
+        let client = JsonRpcClient::new(ClientConfig::new(EndPoint::CustomUrl(server.url())));
+
+        for config in [
+            ProgramAccountsConfig::new().data_slice(8, 40),
+            ProgramAccountsConfig::new().data_slice(0, 4),
+        ] {
+            let err = get_and_deserialize_anchor_program_accounts::<Position>(
+                &client,
+                &Pubkey::new_unique(),
+                "Position",
+                &config,
+            )
+            .await
+            .unwrap_err();
+            assert_eq!(err.code(), "INVALID_INPUT");
+        }
+        assert!(server.requests().is_empty());
+    }
+}
@@ -0,0 +1,750 @@
+use std::{
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use crate::core::{
+    client::ClientConfig,
+    error::{RpcError, Web3WasmError, Web3WasmResult},
+    retry::RetryPolicy,
+};
+use async_trait::async_trait;
+use fluvio_wasm_timer::Delay;
+use futures::{stream, Sink, SinkExt, Stream, StreamExt};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use solana_extra_wasm::account_decoder::UiAccount;
+use solana_sdk::{
+    account::Account, clock::Slot, commitment_config::CommitmentLevel, pubkey::Pubkey,
+    signature::Signature, transaction::TransactionError,
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+pub type WsSink = Pin<Box<dyn Sink<String, Error = Web3WasmError> + Send>>;
+#[cfg(not(target_arch = "wasm32"))]
+pub type WsStream = Pin<Box<dyn Stream<Item = Web3WasmResult<String>> + Send>>;
+#[cfg(not(target_arch = "wasm32"))]
+type NotificationStream<T> = stream::BoxStream<'static, Web3WasmResult<T>>;
+
+#[cfg(target_arch = "wasm32")]
+pub type WsSink = Pin<Box<dyn Sink<String, Error = Web3WasmError>>>;
+#[cfg(target_arch = "wasm32")]
+pub type WsStream = Pin<Box<dyn Stream<Item = Web3WasmResult<String>>>>;
+#[cfg(target_arch = "wasm32")]
+type NotificationStream<T> = stream::LocalBoxStream<'static, Web3WasmResult<T>>;
+
+/// An open websocket, as text frames in both directions.
+pub struct WsConnection {
+    pub sink: WsSink,
+    pub stream: WsStream,
+}
+
+/// Opens websocket connections for [`PubsubClient`].
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait WsConnector: Send + Sync {
+    /// `headers` go with the handshake request where the platform allows it.
+    async fn connect(
+        &self,
+        url: &str,
+        headers: &[(String, String)],
+    ) -> Web3WasmResult<WsConnection>;
+}
+
+/// Native connector, backed by `tokio-tungstenite`.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct TungsteniteConnector;
+
+#[cfg(not(target_arch = "wasm32"))]
+#[async_trait]
+impl WsConnector for TungsteniteConnector {
+    async fn connect(
+        &self,
+        url: &str,
+        headers: &[(String, String)],
+    ) -> Web3WasmResult<WsConnection> {
+        use tokio_tungstenite::tungstenite::{
+            client::IntoClientRequest,
+            http::header::{HeaderName, HeaderValue},
+            Message,
+        };
+
+        let mut request = url.into_client_request().map_err(transport_error)?;
+        for (name, value) in headers {
+            request.headers_mut().append(
+                HeaderName::from_bytes(name.as_bytes()).map_err(Web3WasmError::invalid_input)?,
+                HeaderValue::from_str(value).map_err(Web3WasmError::invalid_input)?,
+            );
+        }
+
+        let (socket, _) = tokio_tungstenite::connect_async(request)
+            .await
+            .map_err(transport_error)?;
+        let (sink, stream) = socket.split();
+
+        let sink = sink
+            .sink_map_err(transport_error)
+            .with(|text: String| async move { Ok::<_, Web3WasmError>(Message::Text(text)) });
+        let stream = stream.filter_map(|message| async move {
+            match message {
+                Ok(Message::Text(text)) => Some(Ok(text)),
+                Ok(Message::Binary(bytes)) => Some(Ok(String::from_utf8_lossy(&bytes).into())),
+                Ok(_) => None,
+                Err(err) => Some(Err(transport_error(err))),
+            }
+        });
+
+        Ok(WsConnection {
+            sink: Box::pin(sink),
+            stream: Box::pin(stream),
+        })
+    }
+}
+
+/// Browser connector, backed by `gloo-net`.
+///
+/// Browsers cannot set headers on a websocket handshake, so headers and bearer
+/// tokens are dropped: put API keys in the URL instead.
+#[cfg(target_arch = "wasm32")]
+pub struct GlooConnector;
+
+#[cfg(target_arch = "wasm32")]
+#[async_trait(?Send)]
+impl WsConnector for GlooConnector {
+    async fn connect(
+        &self,
+        url: &str,
+        _headers: &[(String, String)],
+    ) -> Web3WasmResult<WsConnection> {
+        use gloo_net::websocket::{futures::WebSocket, Message};
+
+        let socket = WebSocket::open(url).map_err(transport_error)?;
+        let (sink, stream) = socket.split();
+
+        let sink = sink
+            .sink_map_err(transport_error)
+            .with(|text: String| async move { Ok::<_, Web3WasmError>(Message::Text(text)) });
+        let stream = stream.map(|message| match message {
+            Ok(Message::Text(text)) => Ok(text),
+            Ok(Message::Bytes(bytes)) => Ok(String::from_utf8_lossy(&bytes).into()),
+            Err(err) => Err(transport_error(err)),
+        });
+
+        Ok(WsConnection {
+            sink: Box::pin(sink),
+            stream: Box::pin(stream),
+        })
+    }
+}
+
+fn transport_error(err: impl std::fmt::Display) -> Web3WasmError {
+    RpcError::Transport(err.to_string()).into()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn default_connector() -> Arc<dyn WsConnector> {
+    Arc::new(TungsteniteConnector)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn default_connector() -> Arc<dyn WsConnector> {
+    Arc::new(GlooConnector)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountNotification {
+    pub slot: Slot,
+    pub account: Account,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignatureNotification {
+    pub slot: Slot,
+    /// `None` when the transaction succeeded.
+    pub err: Option<TransactionError>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogsNotification {
+    pub slot: Slot,
+    pub signature: String,
+    pub err: Option<TransactionError>,
+    pub logs: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LogsFilter {
+    /// Every transaction except simple votes.
+    All,
+    AllWithVotes,
+    /// Transactions mentioning the account.
+    Mentions(Pubkey),
+}
+
+impl LogsFilter {
+    fn to_json(&self) -> Value {
+        match self {
+            LogsFilter::All => json!("all"),
+            LogsFilter::AllWithVotes => json!("allWithVotes"),
+            LogsFilter::Mentions(pubkey) => json!({ "mentions": [pubkey.to_string()] }),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct NotificationContext {
+    slot: Slot,
+}
+
+#[derive(Deserialize)]
+struct NotificationResult<T> {
+    context: NotificationContext,
+    value: T,
+}
+
+#[derive(Deserialize)]
+struct SignatureValue {
+    err: Option<TransactionError>,
+}
+
+#[derive(Deserialize)]
+struct LogsValue {
+    signature: String,
+    err: Option<TransactionError>,
+    logs: Vec<String>,
+}
+
+fn decode_account_notification(result: Value) -> Web3WasmResult<AccountNotification> {
+    let result = serde_json::from_value::<NotificationResult<UiAccount>>(result)?;
+    match result.value.decode::<Account>() {
+        Some(account) => Ok(AccountNotification {
+            slot: result.context.slot,
+            account,
+        }),
+        None => Err(Web3WasmError::decode("Unable to decode account data")),
+    }
+}
+
+fn decode_signature_notification(result: Value) -> Web3WasmResult<SignatureNotification> {
+    let result = serde_json::from_value::<NotificationResult<SignatureValue>>(result)?;
+    Ok(SignatureNotification {
+        slot: result.context.slot,
+        err: result.value.err,
+    })
+}
+
+fn decode_logs_notification(result: Value) -> Web3WasmResult<LogsNotification> {
+    let result = serde_json::from_value::<NotificationResult<LogsValue>>(result)?;
+    Ok(LogsNotification {
+        slot: result.context.slot,
+        signature: result.value.signature,
+        err: result.value.err,
+        logs: result.value.logs,
+    })
+}
+
+/// Websocket subscriptions against a cluster's pubsub endpoint.
+///
+/// Each subscription owns its connection. When the connection drops, it reconnects
+/// with the backoff of its `RetryPolicy` and subscribes again; the stream ends with
+/// an error once `max_attempts` connections in a row failed or dropped before any
+/// notification, or right away with
+/// `RpcError::JsonRpc` when the node rejected the subscription. Dropping the stream closes the connection.
+pub struct PubsubClient {
+    url: String,
+    headers: Vec<(String, String)>,
+    commitment: CommitmentLevel,
+    connector: Arc<dyn WsConnector>,
+    retry_policy: RetryPolicy,
+}
+
+impl PubsubClient {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_owned(),
+            headers: vec![],
+            commitment: CommitmentLevel::Confirmed,
+            connector: default_connector(),
+            retry_policy: RetryPolicy::default(),
+        }
+    }
+
+    /// Uses the websocket URL, commitment, headers and bearer token of `config`.
+    pub fn new_with_config(config: &ClientConfig) -> Self {
+        Self::new(&config.ws_url())
+            .with_commitment(config.commitment)
+            .with_headers(config.http_headers())
+    }
+
+    /// Handshake headers, dropped by the browser connector.
+    pub fn with_headers(mut self, headers: Vec<(String, String)>) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    pub fn with_commitment(mut self, commitment: CommitmentLevel) -> Self {
+        self.commitment = commitment;
+        self
+    }
+
+    pub fn with_connector(mut self, connector: impl WsConnector + 'static) -> Self {
+        self.connector = Arc::new(connector);
+        self
+    }
+
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn account_subscribe(&self, pubkey: &Pubkey) -> Subscription<AccountNotification> {
+        self.subscribe(
+            "accountSubscribe",
+            json!([
+                pubkey.to_string(),
+                { "encoding": "base64", "commitment": self.commitment }
+            ]),
+            "accountNotification",
+            false,
+            decode_account_notification,
+        )
+    }
+
+    /// Yields once, when the transaction reaches the commitment, then ends.
+    pub fn signature_subscribe(
+        &self,
+        signature: &Signature,
+    ) -> Subscription<SignatureNotification> {
+        self.subscribe(
+            "signatureSubscribe",
+            json!([signature.to_string(), { "commitment": self.commitment }]),
+            "signatureNotification",
+            true,
+            decode_signature_notification,
+        )
+    }
+
+    pub fn logs_subscribe(&self, filter: &LogsFilter) -> Subscription<LogsNotification> {
+        self.subscribe(
+            "logsSubscribe",
+            json!([filter.to_json(), { "commitment": self.commitment }]),
+            "logsNotification",
+            false,
+            decode_logs_notification,
+        )
+    }
+
+    fn subscribe<T: Send + 'static>(
+        &self,
+        method: &str,
+        params: Value,
+        notification: &'static str,
+        one_shot: bool,
+        decode: fn(Value) -> Web3WasmResult<T>,
+    ) -> Subscription<T> {
+        let state = SubscriptionState {
+            url: self.url.clone(),
+            headers: self.headers.clone(),
+            connector: self.connector.clone(),
+            retry_policy: self.retry_policy.clone(),
+            request: json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params }),
+            notification,
+            one_shot,
+            decode,
+            subscribed: None,
+            failures: 0,
+            done: false,
+        };
+
+        let inner = stream::unfold(state, |mut state| async move {
+            let item = state.next().await?;
+            Some((item, state))
+        });
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let inner = inner.boxed();
+        #[cfg(target_arch = "wasm32")]
+        let inner = inner.boxed_local();
+
+        Subscription { inner }
+    }
+}
+
+/// Stream of decoded notifications for one subscription.
+pub struct Subscription<T> {
+    inner: NotificationStream<T>,
+}
+
+impl<T> Stream for Subscription<T> {
+    type Item = Web3WasmResult<T>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.as_mut().poll_next(cx)
+    }
+}
+
+struct Subscribed {
+    connection: WsConnection,
+    id: u64,
+}
+
+struct SubscriptionState<T> {
+    url: String,
+    headers: Vec<(String, String)>,
+    connector: Arc<dyn WsConnector>,
+    retry_policy: RetryPolicy,
+    request: Value,
+    notification: &'static str,
+    one_shot: bool,
+    decode: fn(Value) -> Web3WasmResult<T>,
+    subscribed: Option<Subscribed>,
+    /// Failed or dropped connections since the last notification.
+    failures: u32,
+    done: bool,
+}
+
+impl<T> SubscriptionState<T> {
+    async fn next(&mut self) -> Option<Web3WasmResult<T>> {
+        loop {
+            if self.done {
+                return None;
+            }
+
+            let subscribed = match &mut self.subscribed {
+                Some(subscribed) => subscribed,
+                None => {
+                    if self.failures > 0 {
+                        Delay::new(self.retry_policy.backoff(self.failures))
+                            .await
+                            .ok();
+                    }
+                    match self.subscribe().await {
+                        Ok(subscribed) => self.subscribed = Some(subscribed),
+                        // The node refused the subscription itself, no point retrying.
+                        Err(err @ Web3WasmError::Rpc(RpcError::JsonRpc { .. })) => {
+                            self.done = true;
+                            return Some(Err(err));
+                        }
+                        Err(err) => {
+                            if let Some(err) = self.fail(err) {
+                                return Some(Err(err));
+                            }
+                        }
+                    }
+                    continue;
+                }
+            };
+
+            let dropped = match subscribed.connection.stream.next().await {
+                Some(Ok(text)) => {
+                    if let Some(result) =
+                        notification_result(&text, self.notification, subscribed.id)
+                    {
+                        self.failures = 0;
+                        self.done = self.one_shot;
+                        return Some((self.decode)(result));
+                    }
+                    continue;
+                }
+                Some(Err(err)) => err,
+                None => transport_error("Connection closed"),
+            };
+
+            // Dropped connection, subscribe again after a backoff.
+            self.subscribed = None;
+            if let Some(err) = self.fail(dropped) {
+                return Some(Err(err));
+            }
+        }
+    }
+
+    /// Counts a failed or dropped connection, returns `err` once the policy gives up.
+    fn fail(&mut self, err: Web3WasmError) -> Option<Web3WasmError> {
+        self.failures += 1;
+        if self.failures < self.retry_policy.max_attempts {
+            return None;
+        }
+        self.done = true;
+        Some(err)
+    }
+
+    async fn subscribe(&self) -> Web3WasmResult<Subscribed> {
+        let mut connection = self.connector.connect(&self.url, &self.headers).await?;
+        connection.sink.send(self.request.to_string()).await?;
+
+        while let Some(text) = connection.stream.next().await {
+            let response = serde_json::from_str::<Value>(&text?)?;
+            if response["id"] != self.request["id"] {
+                continue;
+            }
+
+            if let Some(error) = response.get("error") {
+                return Err(RpcError::JsonRpc {
+                    code: error["code"].as_i64().unwrap_or_default(),
+                    message: error["message"].as_str().unwrap_or_default().to_owned(),
+                }
+                .into());
+            }
+
+            return match response["result"].as_u64() {
+                Some(id) => Ok(Subscribed { connection, id }),
+                None => Err(Web3WasmError::Decode(format!(
+                    "Invalid subscription response: {}",
+                    response
+                ))),
+            };
+        }
+
+        Err(transport_error("Connection closed before subscribing"))
+    }
+}
+
+/// Returns `params.result` of a notification for `subscription`, if `text` is one.
+fn notification_result(text: &str, method: &str, subscription: u64) -> Option<Value> {
+    let mut message = serde_json::from_str::<Value>(text).ok()?;
+    if message["method"] != method || message["params"]["subscription"] != subscription {
+        return None;
+    }
+    Some(message["params"]["result"].take())
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod test {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::{
+        core::client::EndPoint,
+        tests::ws_server::{StandInWsServer, WsReply},
+    };
+
+    fn fast_policy() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(10),
+            max_delay: Duration::from_millis(50),
+            jitter: false,
+        }
+    }
+
+    fn subscribed(request: &Value, subscription: u64) -> WsReply {
+        WsReply::Message(json!({ "jsonrpc": "2.0", "result": subscription, "id": request["id"] }))
+    }
+
+    fn notification(method: &str, subscription: u64, slot: Slot, value: Value) -> WsReply {
+        WsReply::Message(json!({
+            "jsonrpc": "2.0",
+            "method": method,
+            "params": {
+                "result": { "context": { "slot": slot }, "value": value },
+                "subscription": subscription
+            }
+        }))
+    }
+
+    fn account_value(lamports: u64) -> Value {
+        json!({
+            "data": ["AQID", "base64"],
+            "executable": false,
+            "lamports": lamports,
+            "owner": Pubkey::default().to_string(),
+            "rentEpoch": 0
+        })
+    }
+
+    #[tokio::test]
+    async fn test_account_subscribe() {
+        let server = StandInWsServer::start(|_, request| {
+            vec![
+                subscribed(request, 3),
+                notification("accountNotification", 4, 9, account_value(1)),
+                notification("accountNotification", 3, 10, account_value(2)),
+            ]
+        })
+        .await;
+        let pubkey = Pubkey::new_unique();
+        let client = PubsubClient::new(&server.url()).with_commitment(CommitmentLevel::Finalized);
+
+        let notification = client
+            .account_subscribe(&pubkey)
+            .next()
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(notification.slot, 10);
+        assert_eq!(notification.account.lamports, 2);
+        assert_eq!(notification.account.data, vec![1, 2, 3]);
+
+        let request = &server.requests()[0];
+        assert_eq!(request["method"], "accountSubscribe");
+        assert_eq!(request["params"][0], pubkey.to_string());
+        assert_eq!(request["params"][1]["commitment"], "finalized");
+    }
+
+    #[tokio::test]
+    async fn test_config_headers_on_handshake() {
+        let server = StandInWsServer::start(|_, request| {
+            vec![
+                subscribed(request, 3),
+                notification("accountNotification", 3, 10, account_value(1)),
+            ]
+        })
+        .await;
+        let config = ClientConfig::builder(EndPoint::Mainnet)
+            .ws_url(&server.url())
+            .header("x-api-key", "key")
+            .bearer_token("token")
+            .build();
+        let client = PubsubClient::new_with_config(&config);
+        assert_eq!(client.url(), server.url());
+
+        client
+            .account_subscribe(&Pubkey::new_unique())
+            .next()
+            .await
+            .unwrap()
+            .unwrap();
+
+        let headers = &server.handshake_headers()[0];
+        assert!(headers.contains(&("x-api-key".to_owned(), "key".to_owned())));
+        assert!(headers.contains(&("authorization".to_owned(), "Bearer token".to_owned())));
+    }
+
+    #[tokio::test]
+    async fn test_resubscribe_after_disconnect() {
+        let server = StandInWsServer::start(|connection, request| match connection {
+            0 => vec![
+                subscribed(request, 7),
+                notification("accountNotification", 7, 1, account_value(1)),
+                WsReply::Close,
+            ],
+            _ => vec![
+                subscribed(request, 8),
+                notification("accountNotification", 8, 2, account_value(2)),
+            ],
+        })
+        .await;
+        let client = PubsubClient::new(&server.url()).with_retry_policy(fast_policy());
+
+        let slots = client
+            .account_subscribe(&Pubkey::new_unique())
+            .take(2)
+            .map(|notification| notification.unwrap().slot)
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(slots, vec![1, 2]);
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_when_dropped_before_notifications() {
+        let server =
+            StandInWsServer::start(|_, request| vec![subscribed(request, 7), WsReply::Close]).await;
+        let client = PubsubClient::new(&server.url()).with_retry_policy(fast_policy());
+        let started = std::time::Instant::now();
+
+        let mut subscription = client.account_subscribe(&Pubkey::new_unique());
+
+        assert!(subscription.next().await.unwrap().is_err());
+        assert!(subscription.next().await.is_none());
+        assert_eq!(server.requests().len(), 3);
+        // Backoff of 10ms, then 20ms, between the three connections.
+        assert!(started.elapsed() >= Duration::from_millis(30));
+    }
+
+    #[tokio::test]
+    async fn test_signature_subscribe_ends_after_notification() {
+        let server = StandInWsServer::start(|_, request| {
+            vec![
+                subscribed(request, 1),
+                notification("signatureNotification", 1, 5, json!({ "err": null })),
+            ]
+        })
+        .await;
+        let client = PubsubClient::new(&server.url());
+
+        let mut subscription = client.signature_subscribe(&Signature::default());
+        let notification = subscription.next().await.unwrap().unwrap();
+
+        assert_eq!(notification.slot, 5);
+        assert_eq!(notification.err, None);
+        assert!(subscription.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_logs_subscribe_mentions() {
+        let server = StandInWsServer::start(|_, request| {
+            vec![
+                subscribed(request, 2),
+                notification(
+                    "logsNotification",
+                    2,
+                    6,
+                    json!({
+                        "signature": "sig",
+                        "err": null,
+                        "logs": ["Program log: hello"]
+                    }),
+                ),
+            ]
+        })
+        .await;
+        let program_id = Pubkey::new_unique();
+        let client = PubsubClient::new(&server.url());
+
+        let notification = client
+            .logs_subscribe(&LogsFilter::Mentions(program_id))
+            .next()
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(notification.signature, "sig");
+        assert_eq!(notification.logs, vec!["Program log: hello".to_owned()]);
+        assert_eq!(
+            server.requests()[0]["params"][0],
+            json!({ "mentions": [program_id.to_string()] })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rejected_subscription() {
+        let server = StandInWsServer::start(|_, request| {
+            vec![WsReply::Message(json!({
+                "jsonrpc": "2.0",
+                "error": { "code": -32602, "message": "Invalid params" },
+                "id": request["id"]
+            }))]
+        })
+        .await;
+        let client = PubsubClient::new(&server.url()).with_retry_policy(fast_policy());
+
+        let mut subscription = client.account_subscribe(&Pubkey::new_unique());
+        let err = subscription.next().await.unwrap().unwrap_err();
+
+        assert_eq!(err.to_string(), "RPC error -32602: Invalid params");
+        assert!(subscription.next().await.is_none());
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_when_unreachable() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        drop(listener);
+        let client = PubsubClient::new(&url).with_retry_policy(fast_policy());
+
+        let mut subscription = client.account_subscribe(&Pubkey::new_unique());
+
+        assert!(subscription.next().await.unwrap().is_err());
+        assert!(subscription.next().await.is_none());
+    }
+}
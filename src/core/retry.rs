@@ -0,0 +1,426 @@
+use std::{sync::Mutex, time::Duration};
+
+use async_trait::async_trait;
+use fluvio_wasm_timer::{Delay, Instant};
+use solana_client_wasm::{
+    utils::{rpc_config::RpcKeyedAccount, rpc_filter::TokenAccountsFilter},
+    WasmClient,
+};
+use solana_extra_wasm::account_decoder::parse_token::UiTokenAccount;
+use solana_sdk::{account::Account, clock::Slot, hash::Hash, pubkey::Pubkey};
+
+use crate::core::{
+    account::DataSlice,
+    error::{RpcError, Web3WasmError, Web3WasmResult},
+    program_accounts::ProgramAccountsConfig,
+    rpc::{RpcClient, RpcFuture},
+};
+
+/// How often and how long to wait before retrying a transient RPC error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Total attempts, including the first one.
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    /// Randomize each delay between half and all of its backoff.
+    pub jitter: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            base_delay: Duration::from_millis(250),
+            max_delay: Duration::from_secs(10),
+            jitter: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn no_retry() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Exponential backoff before retrying after the given failed attempt (1-based).
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(31);
+        let delay = self
+            .base_delay
+            .saturating_mul(1u32 << exponent)
+            .min(self.max_delay);
+
+        if !self.jitter {
+            return delay;
+        }
+
+        let half = delay / 2;
+        half + half.mul_f64(random_unit())
+    }
+
+    /// `Retry-After` wins over the computed backoff when the server sent one,
+    /// capped at `max_delay` like the backoff.
+    pub fn delay_for(&self, attempt: u32, err: &Web3WasmError) -> Duration {
+        match err {
+            Web3WasmError::Rpc(RpcError::HttpStatus {
+                retry_after: Some(retry_after),
+                ..
+            }) => (*retry_after).min(self.max_delay),
+            _ => self.backoff(attempt),
+        }
+    }
+}
+
+fn random_unit() -> f64 {
+    let mut bytes = [0u8; 8];
+    match getrandom::getrandom(&mut bytes) {
+        Ok(_) => (u64::from_le_bytes(bytes) >> 11) as f64 / (1u64 << 53) as f64,
+        Err(_) => 0.5,
+    }
+}
+
+/// Lowest rate a [`RateLimiter`] accepts, about one request every 100 seconds.
+pub const MIN_REQUESTS_PER_SECOND: f64 = 0.01;
+
+/// Client-side token bucket, refilled continuously at `requests_per_second`.
+pub struct RateLimiter {
+    capacity: f64,
+    refill_per_second: f64,
+    bucket: Mutex<Bucket>,
+}
+
+struct Bucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl RateLimiter {
+    /// Rates below [`MIN_REQUESTS_PER_SECOND`], zero, negative or NaN, are raised to it.
+    pub fn new(requests_per_second: f64, burst: u32) -> Self {
+        let capacity = f64::from(burst.max(1));
+        Self {
+            capacity,
+            // `f64::max` ignores NaN.
+            refill_per_second: requests_per_second.max(MIN_REQUESTS_PER_SECOND),
+            bucket: Mutex::new(Bucket {
+                tokens: capacity,
+                updated_at: Instant::now(),
+            }),
+        }
+    }
+
+    /// Takes a token, or returns how long until one is available.
+    pub fn try_acquire(&self) -> Result<(), Duration> {
+        let mut bucket = self.bucket.lock().unwrap();
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.updated_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.refill_per_second).min(self.capacity);
+        bucket.updated_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            let missing = 1.0 - bucket.tokens;
+            Err(Duration::from_secs_f64(missing / self.refill_per_second))
+        }
+    }
+
+    pub async fn acquire(&self) {
+        while let Err(wait) = self.try_acquire() {
+            Delay::new(wait).await.ok();
+        }
+    }
+}
+
+/// Retries transient errors of the inner client with exponential backoff.
+pub struct RetryClient<C = WasmClient> {
+    inner: C,
+    policy: RetryPolicy,
+    rate_limiter: Option<RateLimiter>,
+}
+
+impl<C: RpcClient> RetryClient<C> {
+    pub fn new(inner: C, policy: RetryPolicy) -> Self {
+        Self {
+            inner,
+            policy,
+            rate_limiter: None,
+        }
+    }
+
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    pub fn policy(&self) -> &RetryPolicy {
+        &self.policy
+    }
+
+    /// Runs `f` until it succeeds, fails with a non-transient error
+    /// or runs out of attempts.
+    pub async fn execute<'a, T, F>(&'a self, f: F) -> Web3WasmResult<T>
+    where
+        F: Fn() -> RpcFuture<'a, T>,
+    {
+        let mut attempt = 1;
+        loop {
+            if let Some(rate_limiter) = &self.rate_limiter {
+                rate_limiter.acquire().await;
+            }
+
+            match f().await {
+                Ok(value) => return Ok(value),
+                Err(err) if attempt < self.policy.max_attempts && err.is_transient() => {
+                    Delay::new(self.policy.delay_for(attempt, &err)).await.ok();
+                    attempt += 1;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<C: RpcClient> RpcClient for RetryClient<C> {
+    async fn get_account(&self, pubkey: &Pubkey) -> Web3WasmResult<Account> {
+        self.execute(|| self.inner.get_account(pubkey)).await
+    }
+
+    async fn get_account_data(&self, pubkey: &Pubkey) -> Web3WasmResult<Vec<u8>> {
+        self.execute(|| self.inner.get_account_data(pubkey)).await
+    }
+
+    async fn get_multiple_accounts(
+        &self,
+        pubkeys: &[Pubkey],
+    ) -> Web3WasmResult<Vec<Option<Account>>> {
+        self.execute(|| self.inner.get_multiple_accounts(pubkeys))
+            .await
+    }
+
+    async fn get_multiple_accounts_data_slice(
+        &self,
+        pubkeys: &[Pubkey],
+        slice: DataSlice,
+    ) -> Web3WasmResult<Vec<Option<Account>>> {
+        self.execute(|| self.inner.get_multiple_accounts_data_slice(pubkeys, slice))
+            .await
+    }
+
+    async fn get_multiple_token_accounts(
+        &self,
+        pubkeys: &[Pubkey],
+    ) -> Web3WasmResult<Vec<Option<UiTokenAccount>>> {
+        self.execute(|| self.inner.get_multiple_token_accounts(pubkeys))
+            .await
+    }
+
+    async fn get_token_accounts_by_owner(
+        &self,
+        owner: &Pubkey,
+        filter: &TokenAccountsFilter,
+    ) -> Web3WasmResult<Vec<RpcKeyedAccount>> {
+        self.execute(|| self.inner.get_token_accounts_by_owner(owner, filter))
+            .await
+    }
+
+    async fn get_program_accounts(
+        &self,
+        program_id: &Pubkey,
+        config: &ProgramAccountsConfig,
+    ) -> Web3WasmResult<Vec<(Pubkey, Account)>> {
+        self.execute(|| self.inner.get_program_accounts(program_id, config))
+            .await
+    }
+
+    async fn get_balance(&self, pubkey: &Pubkey) -> Web3WasmResult<u64> {
+        self.execute(|| self.inner.get_balance(pubkey)).await
+    }
+
+    async fn get_slot(&self) -> Web3WasmResult<Slot> {
+        self.execute(|| self.inner.get_slot()).await
+    }
+
+    async fn get_health(&self) -> Web3WasmResult<()> {
+        self.execute(|| self.inner.get_health()).await
+    }
+
+    async fn get_genesis_hash(&self) -> Web3WasmResult<Hash> {
+        self.execute(|| self.inner.get_genesis_hash()).await
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod test {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::{
+        core::{
+            client::{ClientConfig, EndPoint},
+            json_rpc::JsonRpcClient,
+        },
+        tests::rpc_server::{StandInResponse, StandInRpcServer},
+    };
+
+    fn fast_policy(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            base_delay: Duration::from_millis(10),
+            max_delay: Duration::from_millis(50),
+            jitter: false,
+        }
+    }
+
+    async fn flaky_server(failures: usize, failure: StandInResponse) -> StandInRpcServer {
+        let calls = Arc::new(AtomicUsize::new(0));
+        StandInRpcServer::start(move |_, _| {
+            if calls.fetch_add(1, Ordering::SeqCst) < failures {
+                failure.clone()
+            } else {
+                StandInResponse::result(json!(1234))
+            }
+        })
+        .await
+    }
+
+    fn json_rpc_client(server: &StandInRpcServer) -> JsonRpcClient {
+        JsonRpcClient::new(ClientConfig::new(EndPoint::CustomUrl(server.url())))
+    }
+
+    #[test]
+    fn test_backoff_without_jitter() {
+        let policy = RetryPolicy {
+            max_attempts: 10,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(500),
+            jitter: false,
+        };
+
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(400));
+        assert_eq!(policy.backoff(4), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_backoff_with_jitter() {
+        let policy = RetryPolicy::default();
+        for attempt in 1..6 {
+            let delay = policy.backoff(attempt);
+            let max = RetryPolicy {
+                jitter: false,
+                ..policy.clone()
+            }
+            .backoff(attempt);
+
+            assert!(delay >= max / 2 && delay <= max);
+        }
+    }
+
+    #[test]
+    fn test_rate_limiter_bucket() {
+        let rate_limiter = RateLimiter::new(10.0, 2);
+
+        assert!(rate_limiter.try_acquire().is_ok());
+        assert!(rate_limiter.try_acquire().is_ok());
+        let wait = rate_limiter.try_acquire().unwrap_err();
+        assert!(wait <= Duration::from_millis(100));
+
+        for requests_per_second in [0.0, -1.0, f64::NAN] {
+            let rate_limiter = RateLimiter::new(requests_per_second, 1);
+            assert!(rate_limiter.try_acquire().is_ok());
+            let wait = rate_limiter.try_acquire().unwrap_err();
+            assert!(wait <= Duration::from_secs_f64(1.0 / MIN_REQUESTS_PER_SECOND));
+        }
+    }
+
+    #[test]
+    fn test_retry_after_is_capped() {
+        let err = Web3WasmError::Rpc(RpcError::HttpStatus {
+            status: 429,
+            retry_after: Some(Duration::from_secs(3600)),
+            body: String::new(),
+        });
+        assert_eq!(fast_policy(2).delay_for(1, &err), Duration::from_millis(50));
+    }
+
+    #[tokio::test]
+    async fn test_retry_on_rate_limit() {
+        let server = flaky_server(2, StandInResponse::status(429)).await;
+        let client = RetryClient::new(json_rpc_client(&server), fast_policy(3));
+
+        assert_eq!(client.get_slot().await.unwrap(), 1234);
+        assert_eq!(server.request_count("getSlot"), 3);
+    }
+
+    #[tokio::test]
+    async fn test_retry_gives_up_after_max_attempts() {
+        let server = flaky_server(5, StandInResponse::status(503)).await;
+        let client = RetryClient::new(json_rpc_client(&server), fast_policy(3));
+
+        let err = client.get_slot().await.unwrap_err();
+        assert!(matches!(
+            err,
+            Web3WasmError::Rpc(RpcError::HttpStatus { status: 503, .. })
+        ));
+        assert_eq!(server.request_count("getSlot"), 3);
+    }
+
+    #[tokio::test]
+    async fn test_no_retry_on_rpc_error() {
+        let server = flaky_server(5, StandInResponse::rpc_error(-32602, "Invalid param")).await;
+        let client = RetryClient::new(json_rpc_client(&server), fast_policy(3));
+
+        assert!(client.get_slot().await.is_err());
+        assert_eq!(server.request_count("getSlot"), 1);
+    }
+
+    #[tokio::test]
+    async fn test_retry_after_header() {
+        let server = flaky_server(
+            1,
+            StandInResponse::status(429).with_header("Retry-After", "1"),
+        )
+        .await;
+        let policy = RetryPolicy {
+            max_delay: Duration::from_secs(2),
+            ..fast_policy(2)
+        };
+        let client = RetryClient::new(json_rpc_client(&server), policy);
+
+        let started_at = Instant::now();
+        assert_eq!(client.get_slot().await.unwrap(), 1234);
+        assert!(started_at.elapsed() >= Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited_client() {
+        let server = flaky_server(0, StandInResponse::status(500)).await;
+        let client = RetryClient::new(json_rpc_client(&server), RetryPolicy::no_retry())
+            .with_rate_limiter(RateLimiter::new(20.0, 1));
+
+        let started_at = Instant::now();
+        for _ in 0..3 {
+            client.get_slot().await.unwrap();
+        }
+        assert!(started_at.elapsed() >= Duration::from_millis(90));
+    }
+}
@@ -0,0 +1,366 @@
+use std::collections::HashSet;
+
+use mpl_token_metadata::state::Metadata;
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::core::{
+    error::{Web3WasmError, Web3WasmResult},
+    token_metadata::{MetadataCollection, MetadataCreator, TokenMetadata},
+};
+
+/// `seller_fee_basis_points` of a 100% royalty.
+pub const MAX_BASIS_POINTS: u16 = 10_000;
+
+/// Royalty fields of a metadata account, from either [`Metadata`] or [`TokenMetadata`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoyaltyInfo {
+    pub update_authority: Pubkey,
+    pub seller_fee_basis_points: u16,
+    pub creators: Vec<MetadataCreator>,
+    pub collection: Option<MetadataCollection>,
+}
+
+impl From<&Metadata> for RoyaltyInfo {
+    fn from(metadata: &Metadata) -> Self {
+        Self {
+            update_authority: metadata.update_authority,
+            seller_fee_basis_points: metadata.data.seller_fee_basis_points,
+            creators: metadata
+                .data
+                .creators
+                .iter()
+                .flatten()
+                .map(|creator| MetadataCreator {
+                    address: creator.address,
+                    verified: creator.verified,
+                    share: creator.share,
+                })
+                .collect(),
+            collection: metadata
+                .collection
+                .as_ref()
+                .map(|collection| MetadataCollection {
+                    verified: collection.verified,
+                    key: collection.key,
+                }),
+        }
+    }
+}
+
+impl From<&TokenMetadata> for RoyaltyInfo {
+    fn from(metadata: &TokenMetadata) -> Self {
+        Self {
+            update_authority: metadata.update_authority,
+            seller_fee_basis_points: metadata.seller_fee_basis_points,
+            creators: metadata.creators.clone().unwrap_or_default(),
+            collection: metadata.collection.clone(),
+        }
+    }
+}
+
+/// One creator's cut of a sale, in the unit of the sale price.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoyaltyPayment {
+    pub creator: Pubkey,
+    pub amount: u64,
+}
+
+/// What a marketplace should know before listing an item.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SuspicionFlag {
+    /// Creator shares do not add up to 100.
+    InvalidShares {
+        total: u32,
+    },
+    DuplicateCreator(Pubkey),
+    RoyaltyAboveMax(u16),
+    /// Creators are listed but none signed, anyone could have listed them.
+    NoVerifiedCreator,
+    UnverifiedCreator(Pubkey),
+    /// A collection is claimed but not verified by its authority.
+    UnverifiedCollection(Pubkey),
+    /// The verified collection is not the expected one.
+    CollectionMismatch {
+        expected: Pubkey,
+        found: Option<Pubkey>,
+    },
+    UpdateAuthorityMismatch {
+        expected: Pubkey,
+        found: Pubkey,
+    },
+    /// The expected creator, e.g. the candy machine, is missing or unverified.
+    CreatorNotVerified(Pubkey),
+}
+
+/// What a genuine item of a collection looks like, fields left `None` are not checked.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TrustedCollection {
+    pub collection: Option<Pubkey>,
+    pub update_authority: Option<Pubkey>,
+    pub verified_creator: Option<Pubkey>,
+}
+
+impl RoyaltyInfo {
+    pub fn total_shares(&self) -> u32 {
+        self.creators
+            .iter()
+            .map(|creator| creator.share as u32)
+            .sum()
+    }
+
+    /// Shares must add up to 100 without duplicate creators, and the fee must not exceed 100%.
+    pub fn validate(&self) -> Web3WasmResult<()> {
+        if self.seller_fee_basis_points > MAX_BASIS_POINTS {
+            return Err(Web3WasmError::InvalidInput(format!(
+                "Royalty of {} basis points is above {MAX_BASIS_POINTS}",
+                self.seller_fee_basis_points
+            )));
+        }
+
+        let total = self.total_shares();
+        if !self.creators.is_empty() && total != 100 {
+            return Err(Web3WasmError::InvalidInput(format!(
+                "Creator shares add up to {total}, expected 100"
+            )));
+        }
+
+        if let Some(creator) = self.duplicate_creators().first() {
+            return Err(Web3WasmError::InvalidInput(format!(
+                "Creator {creator} is listed more than once"
+            )));
+        }
+        Ok(())
+    }
+
+    fn duplicate_creators(&self) -> Vec<Pubkey> {
+        let mut seen = HashSet::new();
+        self.creators
+            .iter()
+            .filter(|creator| !seen.insert(creator.address))
+            .map(|creator| creator.address)
+            .collect()
+    }
+
+    pub fn verified_creators(&self) -> Vec<Pubkey> {
+        self.creators
+            .iter()
+            .filter(|creator| creator.verified)
+            .map(|creator| creator.address)
+            .collect()
+    }
+
+    pub fn unverified_creators(&self) -> Vec<Pubkey> {
+        self.creators
+            .iter()
+            .filter(|creator| !creator.verified)
+            .map(|creator| creator.address)
+            .collect()
+    }
+
+    /// Royalty owed on a sale, in lamports or token base units like `sale_price`.
+    pub fn royalty_amount(&self, sale_price: u64) -> u64 {
+        (sale_price as u128 * self.seller_fee_basis_points.min(MAX_BASIS_POINTS) as u128
+            / MAX_BASIS_POINTS as u128) as u64
+    }
+
+    /// Each creator's cut of [`Self::royalty_amount`], in creator order.
+    ///
+    /// Cuts round down like the Auction House program does, so the dust stays with the seller.
+    pub fn royalty_split(&self, sale_price: u64) -> Web3WasmResult<Vec<RoyaltyPayment>> {
+        self.validate()?;
+        let royalty = self.royalty_amount(sale_price) as u128;
+        Ok(self
+            .creators
+            .iter()
+            .map(|creator| RoyaltyPayment {
+                creator: creator.address,
+                amount: (royalty * creator.share as u128 / 100) as u64,
+            })
+            .collect())
+    }
+
+    /// Everything that looks off, compared to `trusted` when given.
+    pub fn suspicion_flags(&self, trusted: Option<&TrustedCollection>) -> Vec<SuspicionFlag> {
+        let mut flags = vec![];
+
+        let total = self.total_shares();
+        if !self.creators.is_empty() && total != 100 {
+            flags.push(SuspicionFlag::InvalidShares { total });
+        }
+        flags.extend(
+            self.duplicate_creators()
+                .into_iter()
+                .map(SuspicionFlag::DuplicateCreator),
+        );
+        if self.seller_fee_basis_points > MAX_BASIS_POINTS {
+            flags.push(SuspicionFlag::RoyaltyAboveMax(self.seller_fee_basis_points));
+        }
+
+        if !self.creators.is_empty() && self.verified_creators().is_empty() {
+            flags.push(SuspicionFlag::NoVerifiedCreator);
+        }
+        flags.extend(
+            self.unverified_creators()
+                .into_iter()
+                .map(SuspicionFlag::UnverifiedCreator),
+        );
+
+        if let Some(MetadataCollection {
+            verified: false,
+            key,
+        }) = &self.collection
+        {
+            flags.push(SuspicionFlag::UnverifiedCollection(*key));
+        }
+
+        let trusted = match trusted {
+            Some(trusted) => trusted,
+            None => return flags,
+        };
+
+        if let Some(expected) = trusted.collection {
+            let found = self
+                .collection
+                .as_ref()
+                .filter(|collection| collection.verified)
+                .map(|collection| collection.key);
+            if found != Some(expected) {
+                flags.push(SuspicionFlag::CollectionMismatch { expected, found });
+            }
+        }
+
+        if let Some(expected) = trusted.update_authority {
+            if self.update_authority != expected {
+                flags.push(SuspicionFlag::UpdateAuthorityMismatch {
+                    expected,
+                    found: self.update_authority,
+                });
+            }
+        }
+
+        if let Some(creator) = trusted.verified_creator {
+            if !self.verified_creators().contains(&creator) {
+                flags.push(SuspicionFlag::CreatorNotVerified(creator));
+            }
+        }
+
+        flags
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod test {
+    use super::*;
+
+    fn creator(seed: u8, verified: bool, share: u8) -> MetadataCreator {
+        MetadataCreator {
+            address: Pubkey::new_from_array([seed; 32]),
+            verified,
+            share,
+        }
+    }
+
+    fn royalty_info() -> RoyaltyInfo {
+        RoyaltyInfo {
+            update_authority: Pubkey::new_from_array([9; 32]),
+            seller_fee_basis_points: 550,
+            creators: vec![
+                creator(1, true, 0),
+                creator(2, true, 70),
+                creator(3, false, 30),
+            ],
+            collection: Some(MetadataCollection {
+                verified: true,
+                key: Pubkey::new_from_array([8; 32]),
+            }),
+        }
+    }
+
+    #[test]
+    fn test_royalty_split() {
+        let info = royalty_info();
+        info.validate().unwrap();
+        assert_eq!(info.verified_creators().len(), 2);
+        assert_eq!(
+            info.unverified_creators(),
+            vec![Pubkey::new_from_array([3; 32])]
+        );
+
+        // 5.5% of 1.000000001 SOL.
+        assert_eq!(info.royalty_amount(1_000_000_001), 55_000_000);
+        let split = info.royalty_split(1_000_000_001).unwrap();
+        assert_eq!(
+            split
+                .iter()
+                .map(|payment| payment.amount)
+                .collect::<Vec<_>>(),
+            vec![0, 38_500_000, 16_500_000]
+        );
+
+        // Token units round down per creator.
+        let split = info.royalty_split(1_001).unwrap();
+        assert_eq!(
+            split
+                .iter()
+                .map(|payment| payment.amount)
+                .collect::<Vec<_>>(),
+            vec![0, 38, 16]
+        );
+
+        let mut invalid = info;
+        invalid.creators[1].share = 60;
+        assert_eq!(
+            invalid.royalty_split(100).unwrap_err().code(),
+            "INVALID_INPUT"
+        );
+    }
+
+    #[test]
+    fn test_suspicion_flags() {
+        let info = royalty_info();
+        assert_eq!(
+            info.suspicion_flags(None),
+            vec![SuspicionFlag::UnverifiedCreator(Pubkey::new_from_array(
+                [3; 32]
+            ))]
+        );
+
+        // A copy claiming the collection without its verification.
+        let mut spoof = royalty_info();
+        spoof.update_authority = Pubkey::new_unique();
+        spoof.creators = vec![creator(1, false, 50), creator(1, false, 50)];
+        spoof.collection.as_mut().unwrap().verified = false;
+        let trusted = TrustedCollection {
+            collection: Some(Pubkey::new_from_array([8; 32])),
+            update_authority: Some(Pubkey::new_from_array([9; 32])),
+            verified_creator: Some(Pubkey::new_from_array([1; 32])),
+        };
+
+        let flags = spoof.suspicion_flags(Some(&trusted));
+        assert!(
+            flags.contains(&SuspicionFlag::DuplicateCreator(Pubkey::new_from_array(
+                [1; 32]
+            )))
+        );
+        assert!(flags.contains(&SuspicionFlag::NoVerifiedCreator));
+        assert!(flags.contains(&SuspicionFlag::UnverifiedCollection(
+            Pubkey::new_from_array([8; 32])
+        )));
+        assert!(flags.contains(&SuspicionFlag::CollectionMismatch {
+            expected: Pubkey::new_from_array([8; 32]),
+            found: None,
+        }));
+        assert!(flags.contains(&SuspicionFlag::UpdateAuthorityMismatch {
+            expected: Pubkey::new_from_array([9; 32]),
+            found: spoof.update_authority,
+        }));
+        assert!(
+            flags.contains(&SuspicionFlag::CreatorNotVerified(Pubkey::new_from_array(
+                [1; 32]
+            )))
+        );
+        assert!(royalty_info().suspicion_flags(Some(&trusted)).len() == 1);
+    }
+}
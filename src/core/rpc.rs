@@ -0,0 +1,217 @@
+use async_trait::async_trait;
+use solana_client_wasm::{
+    utils::{
+        rpc_config::{RpcAccountInfoConfig, RpcKeyedAccount, RpcProgramAccountsConfig},
+        rpc_filter::{Memcmp, RpcFilterType, TokenAccountsFilter},
+    },
+    WasmClient,
+};
+use solana_extra_wasm::account_decoder::{
+    parse_token::UiTokenAccount, UiAccountEncoding, UiDataSliceConfig,
+};
+use solana_sdk::{account::Account, clock::Slot, hash::Hash, pubkey::Pubkey};
+
+use crate::core::{
+    account::DataSlice,
+    error::{Web3WasmError, Web3WasmResult},
+    program_accounts::{AccountFilter, ProgramAccountsConfig},
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) type RpcFuture<'a, T> = futures::future::BoxFuture<'a, Web3WasmResult<T>>;
+
+#[cfg(target_arch = "wasm32")]
+pub(crate) type RpcFuture<'a, T> = futures::future::LocalBoxFuture<'a, Web3WasmResult<T>>;
+
+/// The RPC calls this crate relies on.
+///
+/// Implemented for `WasmClient` and for the client wrappers in this crate, so
+/// every helper that accepts `&impl RpcClient` keeps accepting a `&WasmClient`.
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait RpcClient: Send + Sync {
+    async fn get_account(&self, pubkey: &Pubkey) -> Web3WasmResult<Account>;
+
+    async fn get_account_data(&self, pubkey: &Pubkey) -> Web3WasmResult<Vec<u8>> {
+        Ok(self.get_account(pubkey).await?.data)
+    }
+
+    async fn get_multiple_accounts(
+        &self,
+        pubkeys: &[Pubkey],
+    ) -> Web3WasmResult<Vec<Option<Account>>>;
+
+    /// `getMultipleAccounts` returning only `slice` of each account's data.
+    async fn get_multiple_accounts_data_slice(
+        &self,
+        pubkeys: &[Pubkey],
+        slice: DataSlice,
+    ) -> Web3WasmResult<Vec<Option<Account>>>;
+
+    async fn get_multiple_token_accounts(
+        &self,
+        pubkeys: &[Pubkey],
+    ) -> Web3WasmResult<Vec<Option<UiTokenAccount>>>;
+
+    async fn get_token_accounts_by_owner(
+        &self,
+        owner: &Pubkey,
+        filter: &TokenAccountsFilter,
+    ) -> Web3WasmResult<Vec<RpcKeyedAccount>>;
+
+    async fn get_program_accounts(
+        &self,
+        program_id: &Pubkey,
+        config: &ProgramAccountsConfig,
+    ) -> Web3WasmResult<Vec<(Pubkey, Account)>>;
+
+    async fn get_balance(&self, pubkey: &Pubkey) -> Web3WasmResult<u64>;
+
+    async fn get_slot(&self) -> Web3WasmResult<Slot>;
+
+    async fn get_health(&self) -> Web3WasmResult<()>;
+
+    async fn get_genesis_hash(&self) -> Web3WasmResult<Hash>;
+}
+
+/// `WasmClient` reports a missing account as a `Client error:`.
+fn account_error(err: impl std::fmt::Display) -> Web3WasmError {
+    match err.to_string() {
+        message if message.starts_with("Client error:") => Web3WasmError::NotFound(message),
+        message => Web3WasmError::client(message),
+    }
+}
+
+fn data_slice_config(slice: DataSlice) -> UiDataSliceConfig {
+    UiDataSliceConfig {
+        offset: slice.offset,
+        length: slice.length,
+    }
+}
+
+fn program_accounts_config(config: &ProgramAccountsConfig) -> RpcProgramAccountsConfig {
+    let filters = config
+        .filters
+        .iter()
+        .map(|filter| match filter {
+            AccountFilter::Memcmp { offset, bytes } => {
+                RpcFilterType::Memcmp(Memcmp::new_base58_encoded(*offset, bytes))
+            }
+            AccountFilter::DataSize(size) => RpcFilterType::DataSize(*size),
+        })
+        .collect();
+
+    RpcProgramAccountsConfig {
+        filters: Some(filters),
+        account_config: RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            data_slice: config.data_slice.map(data_slice_config),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl RpcClient for WasmClient {
+    async fn get_account(&self, pubkey: &Pubkey) -> Web3WasmResult<Account> {
+        WasmClient::get_account(self, pubkey)
+            .await
+            .map_err(account_error)
+    }
+
+    async fn get_account_data(&self, pubkey: &Pubkey) -> Web3WasmResult<Vec<u8>> {
+        WasmClient::get_account_data(self, pubkey)
+            .await
+            .map_err(account_error)
+    }
+
+    async fn get_multiple_accounts(
+        &self,
+        pubkeys: &[Pubkey],
+    ) -> Web3WasmResult<Vec<Option<Account>>> {
+        WasmClient::get_multiple_accounts(self, pubkeys)
+            .await
+            .map_err(Web3WasmError::client)
+    }
+
+    async fn get_multiple_accounts_data_slice(
+        &self,
+        pubkeys: &[Pubkey],
+        slice: DataSlice,
+    ) -> Web3WasmResult<Vec<Option<Account>>> {
+        let config = RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            data_slice: Some(data_slice_config(slice)),
+            ..Default::default()
+        };
+        WasmClient::get_multiple_accounts_with_config(self, pubkeys, config)
+            .await
+            .map_err(Web3WasmError::client)
+    }
+
+    async fn get_multiple_token_accounts(
+        &self,
+        pubkeys: &[Pubkey],
+    ) -> Web3WasmResult<Vec<Option<UiTokenAccount>>> {
+        WasmClient::get_multiple_token_accounts(self, pubkeys)
+            .await
+            .map_err(Web3WasmError::client)
+    }
+
+    async fn get_token_accounts_by_owner(
+        &self,
+        owner: &Pubkey,
+        filter: &TokenAccountsFilter,
+    ) -> Web3WasmResult<Vec<RpcKeyedAccount>> {
+        let filter = match filter {
+            TokenAccountsFilter::Mint(mint) => TokenAccountsFilter::Mint(*mint),
+            TokenAccountsFilter::ProgramId(program_id) => {
+                TokenAccountsFilter::ProgramId(*program_id)
+            }
+        };
+        WasmClient::get_token_accounts_by_owner(self, owner, filter)
+            .await
+            .map_err(Web3WasmError::client)
+    }
+
+    async fn get_program_accounts(
+        &self,
+        program_id: &Pubkey,
+        config: &ProgramAccountsConfig,
+    ) -> Web3WasmResult<Vec<(Pubkey, Account)>> {
+        WasmClient::get_program_accounts_with_config(
+            self,
+            program_id,
+            program_accounts_config(config),
+        )
+        .await
+        .map_err(Web3WasmError::client)
+    }
+
+    async fn get_balance(&self, pubkey: &Pubkey) -> Web3WasmResult<u64> {
+        WasmClient::get_balance(self, pubkey)
+            .await
+            .map_err(Web3WasmError::client)
+    }
+
+    async fn get_slot(&self) -> Web3WasmResult<Slot> {
+        WasmClient::get_slot(self)
+            .await
+            .map_err(Web3WasmError::client)
+    }
+
+    async fn get_health(&self) -> Web3WasmResult<()> {
+        WasmClient::get_health(self)
+            .await
+            .map(|_| ())
+            .map_err(Web3WasmError::client)
+    }
+
+    async fn get_genesis_hash(&self) -> Web3WasmResult<Hash> {
+        WasmClient::get_genesis_hash(self)
+            .await
+            .map_err(Web3WasmError::client)
+    }
+}
@@ -0,0 +1,534 @@
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::core::{
+    error::{Web3WasmError, Web3WasmResult},
+    token_metadata::BorshReader,
+};
+
+// Layout -------------------------------------
+
+/// Mints are padded to the size of a token account, so both keep their `AccountType` here.
+pub const ACCOUNT_TYPE_OFFSET: usize = 165;
+
+/// First TLV entry, right after the `AccountType` byte.
+pub const TLV_START: usize = ACCOUNT_TYPE_OFFSET + 1;
+
+/// `ExtensionType::TransferFeeConfig`.
+pub const TRANSFER_FEE_CONFIG_EXTENSION: u16 = 1;
+/// `ExtensionType::MintCloseAuthority`.
+pub const MINT_CLOSE_AUTHORITY_EXTENSION: u16 = 3;
+/// `ExtensionType::DefaultAccountState`.
+pub const DEFAULT_ACCOUNT_STATE_EXTENSION: u16 = 6;
+/// `ExtensionType::NonTransferable`.
+pub const NON_TRANSFERABLE_EXTENSION: u16 = 9;
+/// `ExtensionType::InterestBearingConfig`.
+pub const INTEREST_BEARING_CONFIG_EXTENSION: u16 = 10;
+/// `ExtensionType::PermanentDelegate`.
+pub const PERMANENT_DELEGATE_EXTENSION: u16 = 12;
+/// `ExtensionType::TransferHook`.
+pub const TRANSFER_HOOK_EXTENSION: u16 = 14;
+/// `ExtensionType::MetadataPointer`.
+pub const METADATA_POINTER_EXTENSION: u16 = 18;
+/// `ExtensionType::TokenMetadata`.
+pub const TOKEN_METADATA_EXTENSION: u16 = 19;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AccountType {
+    Mint,
+    Account,
+}
+
+/// `AccountType` of Token-2022 account data, `None` for accounts without extensions.
+pub fn account_type(data: &[u8]) -> Option<AccountType> {
+    match data.get(ACCOUNT_TYPE_OFFSET)? {
+        1 => Some(AccountType::Mint),
+        2 => Some(AccountType::Account),
+        _ => None,
+    }
+}
+
+/// One extension of a Token-2022 account, value still encoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TlvEntry<'a> {
+    pub extension_type: u16,
+    pub value: &'a [u8],
+}
+
+/// Walks the TLV extensions of Token-2022 account data, `u16` type then `u16` length.
+///
+/// Reads raw bytes so extensions newer than the bundled `spl-token-2022` are still found.
+/// Stops at the zeroed space left for extensions that are not initialized yet.
+pub fn parse_tlv(data: &[u8]) -> Web3WasmResult<Vec<TlvEntry<'_>>> {
+    let mut entries = vec![];
+    let mut offset = TLV_START;
+    while offset.checked_add(4).map_or(false, |end| end <= data.len()) {
+        let extension_type = u16::from_le_bytes([data[offset], data[offset + 1]]);
+        let length = u16::from_le_bytes([data[offset + 2], data[offset + 3]]) as usize;
+        if extension_type == 0 {
+            break;
+        }
+
+        let value = (offset + 4)
+            .checked_add(length)
+            .and_then(|end| data.get(offset + 4..end))
+            .ok_or_else(|| {
+                Web3WasmError::Decode(format!(
+                    "Extension {extension_type} at offset {offset} overruns the account"
+                ))
+            })?;
+        entries.push(TlvEntry {
+            extension_type,
+            value,
+        });
+        offset += 4 + length;
+    }
+    Ok(entries)
+}
+
+/// Value of the first `extension_type` entry.
+pub fn find_extension(data: &[u8], extension_type: u16) -> Web3WasmResult<Option<&[u8]>> {
+    Ok(parse_tlv(data)?
+        .into_iter()
+        .find(|entry| entry.extension_type == extension_type)
+        .map(|entry| entry.value))
+}
+
+/// `OptionalNonZeroPubkey`: all zeroes means none.
+pub(crate) fn optional_pubkey(reader: &mut BorshReader) -> Web3WasmResult<Option<Pubkey>> {
+    let pubkey = reader.pubkey()?;
+    Ok((pubkey != Pubkey::default()).then_some(pubkey))
+}
+
+// Metadata -------------------------------------
+
+/// Where a mint says its metadata lives.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MetadataPointer {
+    pub authority: Option<Pubkey>,
+    pub metadata_address: Option<Pubkey>,
+}
+
+impl MetadataPointer {
+    pub fn unpack(value: &[u8]) -> Web3WasmResult<Self> {
+        let mut reader = BorshReader::new(value);
+        Ok(Self {
+            authority: optional_pubkey(&mut reader)?,
+            metadata_address: optional_pubkey(&mut reader)?,
+        })
+    }
+}
+
+/// Token-2022 `TokenMetadata` extension, as in `spl-token-metadata-interface`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Token2022Metadata {
+    pub update_authority: Option<Pubkey>,
+    pub mint: Pubkey,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub additional_metadata: Vec<(String, String)>,
+}
+
+impl Token2022Metadata {
+    pub fn unpack(value: &[u8]) -> Web3WasmResult<Self> {
+        let mut reader = BorshReader::new(value);
+        Ok(Self {
+            update_authority: optional_pubkey(&mut reader)?,
+            mint: reader.pubkey()?,
+            name: reader.string()?,
+            symbol: reader.string()?,
+            uri: reader.string()?,
+            additional_metadata: reader.vec(|reader| Ok((reader.string()?, reader.string()?)))?,
+        })
+    }
+}
+
+/// Metadata extensions of a Token-2022 mint, `None` when absent.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MintMetadataExtensions {
+    pub pointer: Option<MetadataPointer>,
+    pub metadata: Option<Token2022Metadata>,
+}
+
+impl MintMetadataExtensions {
+    pub fn unpack(mint_data: &[u8]) -> Web3WasmResult<Self> {
+        if account_type(mint_data) != Some(AccountType::Mint) {
+            return Ok(Self::default());
+        }
+
+        let mut extensions = Self::default();
+        for entry in parse_tlv(mint_data)? {
+            match entry.extension_type {
+                METADATA_POINTER_EXTENSION => {
+                    extensions.pointer = Some(MetadataPointer::unpack(entry.value)?)
+                }
+                TOKEN_METADATA_EXTENSION => {
+                    extensions.metadata = Some(Token2022Metadata::unpack(entry.value)?)
+                }
+                _ => {}
+            }
+        }
+        Ok(extensions)
+    }
+}
+
+// Mint extensions -------------------------------------
+
+/// Fee schedule of a `TransferFeeConfig`, effective from `epoch`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransferFee {
+    pub epoch: u64,
+    pub maximum_fee: u64,
+    pub transfer_fee_basis_points: u16,
+}
+
+impl TransferFee {
+    fn read(reader: &mut BorshReader) -> Web3WasmResult<Self> {
+        Ok(Self {
+            epoch: reader.u64()?,
+            maximum_fee: reader.u64()?,
+            transfer_fee_basis_points: reader.u16()?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransferFeeConfig {
+    pub transfer_fee_config_authority: Option<Pubkey>,
+    pub withdraw_withheld_authority: Option<Pubkey>,
+    pub withheld_amount: u64,
+    pub older_transfer_fee: TransferFee,
+    pub newer_transfer_fee: TransferFee,
+}
+
+impl TransferFeeConfig {
+    pub fn unpack(value: &[u8]) -> Web3WasmResult<Self> {
+        let mut reader = BorshReader::new(value);
+        Ok(Self {
+            transfer_fee_config_authority: optional_pubkey(&mut reader)?,
+            withdraw_withheld_authority: optional_pubkey(&mut reader)?,
+            withheld_amount: reader.u64()?,
+            older_transfer_fee: TransferFee::read(&mut reader)?,
+            newer_transfer_fee: TransferFee::read(&mut reader)?,
+        })
+    }
+
+    /// The fee in force at `epoch`: the newer one once its epoch is reached.
+    pub fn fee_at(&self, epoch: u64) -> &TransferFee {
+        if epoch >= self.newer_transfer_fee.epoch {
+            &self.newer_transfer_fee
+        } else {
+            &self.older_transfer_fee
+        }
+    }
+}
+
+/// State new token accounts of a mint start in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AccountState {
+    Uninitialized,
+    Initialized,
+    Frozen,
+}
+
+impl AccountState {
+    pub fn unpack(value: &[u8]) -> Web3WasmResult<Self> {
+        match BorshReader::new(value).u8()? {
+            0 => Ok(Self::Uninitialized),
+            1 => Ok(Self::Initialized),
+            2 => Ok(Self::Frozen),
+            state => Err(Web3WasmError::Decode(format!(
+                "Invalid account state {state}"
+            ))),
+        }
+    }
+}
+
+/// Rates are in basis points per year, timestamps in Unix seconds.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InterestBearingConfig {
+    pub rate_authority: Option<Pubkey>,
+    pub initialization_timestamp: i64,
+    pub pre_update_average_rate: i16,
+    pub last_update_timestamp: i64,
+    pub current_rate: i16,
+}
+
+impl InterestBearingConfig {
+    pub fn unpack(value: &[u8]) -> Web3WasmResult<Self> {
+        let mut reader = BorshReader::new(value);
+        Ok(Self {
+            rate_authority: optional_pubkey(&mut reader)?,
+            initialization_timestamp: reader.u64()? as i64,
+            pre_update_average_rate: reader.u16()? as i16,
+            last_update_timestamp: reader.u64()? as i64,
+            current_rate: reader.u16()? as i16,
+        })
+    }
+}
+
+/// Program invoked on every transfer of the mint.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransferHook {
+    pub authority: Option<Pubkey>,
+    pub program_id: Option<Pubkey>,
+}
+
+impl TransferHook {
+    pub fn unpack(value: &[u8]) -> Web3WasmResult<Self> {
+        let mut reader = BorshReader::new(value);
+        Ok(Self {
+            authority: optional_pubkey(&mut reader)?,
+            program_id: optional_pubkey(&mut reader)?,
+        })
+    }
+}
+
+/// A Token-2022 mint extension with its parsed config.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MintExtension {
+    TransferFeeConfig(TransferFeeConfig),
+    MintCloseAuthority(Option<Pubkey>),
+    DefaultAccountState(AccountState),
+    NonTransferable,
+    InterestBearingConfig(InterestBearingConfig),
+    PermanentDelegate(Option<Pubkey>),
+    TransferHook(TransferHook),
+    MetadataPointer(MetadataPointer),
+    TokenMetadata(Token2022Metadata),
+    /// An extension this crate does not parse, value kept encoded.
+    Other {
+        extension_type: u16,
+        value: Vec<u8>,
+    },
+}
+
+impl MintExtension {
+    pub fn unpack(entry: &TlvEntry) -> Web3WasmResult<Self> {
+        let value = entry.value;
+        Ok(match entry.extension_type {
+            TRANSFER_FEE_CONFIG_EXTENSION => {
+                Self::TransferFeeConfig(TransferFeeConfig::unpack(value)?)
+            }
+            MINT_CLOSE_AUTHORITY_EXTENSION => {
+                Self::MintCloseAuthority(optional_pubkey(&mut BorshReader::new(value))?)
+            }
+            DEFAULT_ACCOUNT_STATE_EXTENSION => {
+                Self::DefaultAccountState(AccountState::unpack(value)?)
+            }
+            NON_TRANSFERABLE_EXTENSION => Self::NonTransferable,
+            INTEREST_BEARING_CONFIG_EXTENSION => {
+                Self::InterestBearingConfig(InterestBearingConfig::unpack(value)?)
+            }
+            PERMANENT_DELEGATE_EXTENSION => {
+                Self::PermanentDelegate(optional_pubkey(&mut BorshReader::new(value))?)
+            }
+            TRANSFER_HOOK_EXTENSION => Self::TransferHook(TransferHook::unpack(value)?),
+            METADATA_POINTER_EXTENSION => Self::MetadataPointer(MetadataPointer::unpack(value)?),
+            TOKEN_METADATA_EXTENSION => Self::TokenMetadata(Token2022Metadata::unpack(value)?),
+            extension_type => Self::Other {
+                extension_type,
+                value: value.to_vec(),
+            },
+        })
+    }
+
+    pub fn extension_type(&self) -> u16 {
+        match self {
+            Self::TransferFeeConfig(_) => TRANSFER_FEE_CONFIG_EXTENSION,
+            Self::MintCloseAuthority(_) => MINT_CLOSE_AUTHORITY_EXTENSION,
+            Self::DefaultAccountState(_) => DEFAULT_ACCOUNT_STATE_EXTENSION,
+            Self::NonTransferable => NON_TRANSFERABLE_EXTENSION,
+            Self::InterestBearingConfig(_) => INTEREST_BEARING_CONFIG_EXTENSION,
+            Self::PermanentDelegate(_) => PERMANENT_DELEGATE_EXTENSION,
+            Self::TransferHook(_) => TRANSFER_HOOK_EXTENSION,
+            Self::MetadataPointer(_) => METADATA_POINTER_EXTENSION,
+            Self::TokenMetadata(_) => TOKEN_METADATA_EXTENSION,
+            Self::Other { extension_type, .. } => *extension_type,
+        }
+    }
+}
+
+/// Every extension of Token-2022 mint data, in account order.
+pub fn unpack_mint_extensions(mint_data: &[u8]) -> Web3WasmResult<Vec<MintExtension>> {
+    if account_type(mint_data) != Some(AccountType::Mint) {
+        return Ok(vec![]);
+    }
+    parse_tlv(mint_data)?
+        .iter()
+        .map(MintExtension::unpack)
+        .collect()
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) mod test {
+    use super::*;
+
+    fn string(data: &mut Vec<u8>, value: &str) {
+        data.extend((value.len() as u32).to_le_bytes());
+        data.extend(value.as_bytes());
+    }
+
+    /// Token-2022 mint data with `extensions` as `(type, value)` TLV entries.
+    pub(crate) fn mint_with_extensions(extensions: &[(u16, Vec<u8>)]) -> Vec<u8> {
+        let mut data = vec![0; ACCOUNT_TYPE_OFFSET];
+        data[44] = 6; // decimals
+        data[45] = 1; // is_initialized
+        data.push(1);
+        for (extension_type, value) in extensions {
+            data.extend(extension_type.to_le_bytes());
+            data.extend((value.len() as u16).to_le_bytes());
+            data.extend(value);
+        }
+        data
+    }
+
+    pub(crate) fn metadata_pointer(metadata_address: &Pubkey) -> (u16, Vec<u8>) {
+        let mut value = vec![0; 32];
+        value.extend(metadata_address.to_bytes());
+        (METADATA_POINTER_EXTENSION, value)
+    }
+
+    pub(crate) fn token_metadata(mint: &Pubkey, name: &str, symbol: &str) -> (u16, Vec<u8>) {
+        let mut value = vec![7; 32];
+        value.extend(mint.to_bytes());
+        string(&mut value, name);
+        string(&mut value, symbol);
+        string(&mut value, "https://example.com/token.json");
+        value.extend(1u32.to_le_bytes());
+        string(&mut value, "website");
+        string(&mut value, "https://example.com");
+        (TOKEN_METADATA_EXTENSION, value)
+    }
+
+    /// `basis_points` until epoch 100, twice that from then on.
+    pub(crate) fn transfer_fee_config(basis_points: u16, maximum_fee: u64) -> (u16, Vec<u8>) {
+        let mut value = vec![1; 32];
+        value.extend([0; 32]);
+        value.extend(5u64.to_le_bytes());
+        for (epoch, basis_points) in [(0u64, basis_points), (100, basis_points * 2)] {
+            value.extend(epoch.to_le_bytes());
+            value.extend(maximum_fee.to_le_bytes());
+            value.extend(basis_points.to_le_bytes());
+        }
+        (TRANSFER_FEE_CONFIG_EXTENSION, value)
+    }
+
+    #[test]
+    fn test_parse_tlv() {
+        let mint = Pubkey::new_unique();
+        let mut data = mint_with_extensions(&[
+            (3, vec![9; 32]), // MintCloseAuthority
+            metadata_pointer(&mint),
+            token_metadata(&mint, "Paxos Gold", "PAXG"),
+        ]);
+        // Space reserved for an extension that is not initialized yet.
+        data.extend([0; 8]);
+
+        assert_eq!(account_type(&data), Some(AccountType::Mint));
+        let entries = parse_tlv(&data).unwrap();
+        assert_eq!(
+            entries
+                .iter()
+                .map(|entry| entry.extension_type)
+                .collect::<Vec<_>>(),
+            vec![3, METADATA_POINTER_EXTENSION, TOKEN_METADATA_EXTENSION]
+        );
+
+        let extensions = MintMetadataExtensions::unpack(&data).unwrap();
+        let pointer = extensions.pointer.unwrap();
+        assert_eq!(pointer.authority, None);
+        assert_eq!(pointer.metadata_address, Some(mint));
+        let metadata = extensions.metadata.unwrap();
+        assert_eq!(
+            metadata.update_authority,
+            Some(Pubkey::new_from_array([7; 32]))
+        );
+        assert_eq!(metadata.name, "Paxos Gold");
+        assert_eq!(metadata.symbol, "PAXG");
+        assert_eq!(
+            metadata.additional_metadata,
+            vec![("website".to_owned(), "https://example.com".to_owned())]
+        );
+
+        data.truncate(data.len() - 20);
+        assert!(parse_tlv(&data).is_err());
+        assert_eq!(account_type(&[0; 82]), None);
+        assert!(parse_tlv(&[0; 82]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_unpack_mint_extensions() {
+        let delegate = Pubkey::new_unique();
+        let hook = Pubkey::new_unique();
+        let mut interest = vec![0; 32];
+        interest.extend(1_700_000_000i64.to_le_bytes());
+        interest.extend(250i16.to_le_bytes());
+        interest.extend(1_700_000_100i64.to_le_bytes());
+        interest.extend((-50i16).to_le_bytes());
+        let mut transfer_hook = vec![0; 32];
+        transfer_hook.extend(hook.to_bytes());
+
+        let data = mint_with_extensions(&[
+            transfer_fee_config(100, 5_000),
+            (DEFAULT_ACCOUNT_STATE_EXTENSION, vec![2]),
+            (NON_TRANSFERABLE_EXTENSION, vec![]),
+            (INTEREST_BEARING_CONFIG_EXTENSION, interest),
+            (PERMANENT_DELEGATE_EXTENSION, delegate.to_bytes().to_vec()),
+            (TRANSFER_HOOK_EXTENSION, transfer_hook),
+            (7, vec![]), // ImmutableOwner, an account extension
+        ]);
+        let extensions = unpack_mint_extensions(&data).unwrap();
+        assert_eq!(extensions.len(), 7);
+
+        let fees = match &extensions[0] {
+            MintExtension::TransferFeeConfig(fees) => fees,
+            other => panic!("unexpected {other:?}"),
+        };
+        assert_eq!(
+            fees.transfer_fee_config_authority,
+            Some(Pubkey::new_from_array([1; 32]))
+        );
+        assert_eq!(fees.withdraw_withheld_authority, None);
+        assert_eq!(fees.withheld_amount, 5);
+        assert_eq!(fees.fee_at(99).transfer_fee_basis_points, 100);
+        assert_eq!(fees.fee_at(100).transfer_fee_basis_points, 200);
+
+        assert_eq!(
+            extensions[1],
+            MintExtension::DefaultAccountState(AccountState::Frozen)
+        );
+        assert_eq!(extensions[2], MintExtension::NonTransferable);
+        assert_eq!(
+            extensions[3],
+            MintExtension::InterestBearingConfig(InterestBearingConfig {
+                rate_authority: None,
+                initialization_timestamp: 1_700_000_000,
+                pre_update_average_rate: 250,
+                last_update_timestamp: 1_700_000_100,
+                current_rate: -50,
+            })
+        );
+        assert_eq!(
+            extensions[4],
+            MintExtension::PermanentDelegate(Some(delegate))
+        );
+        assert_eq!(
+            extensions[5],
+            MintExtension::TransferHook(TransferHook {
+                authority: None,
+                program_id: Some(hook),
+            })
+        );
+        assert_eq!(extensions[6].extension_type(), 7);
+
+        assert!(unpack_mint_extensions(&[0; 82]).unwrap().is_empty());
+        assert!(unpack_mint_extensions(&mint_with_extensions(&[(
+            DEFAULT_ACCOUNT_STATE_EXTENSION,
+            vec![3]
+        )]))
+        .is_err());
+    }
+}
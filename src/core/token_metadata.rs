@@ -0,0 +1,485 @@
+use std::collections::HashMap;
+
+use mpl_token_metadata::pda::find_metadata_account;
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+#[cfg(feature = "wasm_bindgen")]
+use wasm_bindgen::prelude::*;
+
+use crate::core::{
+    account::{get_multiple_accounts_chunked, AccountResult},
+    error::{Web3WasmError, Web3WasmResult},
+    rpc::RpcClient,
+};
+
+// Type -------------------------------------
+
+/// `token_standard` of a metadata account, in on-chain order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TokenStandard {
+    NonFungible,
+    FungibleAsset,
+    Fungible,
+    NonFungibleEdition,
+    ProgrammableNonFungible,
+    ProgrammableNonFungibleEdition,
+}
+
+/// How a wallet should treat a mint.
+#[cfg_attr(feature = "wasm_bindgen", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AssetClass {
+    Fungible,
+    FungibleAsset,
+    NonFungible,
+    /// Also covers editions of programmable NFTs, which transfer through the same rules.
+    ProgrammableNonFungible,
+    NonFungibleEdition,
+}
+
+impl From<TokenStandard> for AssetClass {
+    fn from(token_standard: TokenStandard) -> Self {
+        match token_standard {
+            TokenStandard::NonFungible => AssetClass::NonFungible,
+            TokenStandard::FungibleAsset => AssetClass::FungibleAsset,
+            TokenStandard::Fungible => AssetClass::Fungible,
+            TokenStandard::NonFungibleEdition => AssetClass::NonFungibleEdition,
+            TokenStandard::ProgrammableNonFungible
+            | TokenStandard::ProgrammableNonFungibleEdition => AssetClass::ProgrammableNonFungible,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MetadataCreator {
+    pub address: Pubkey,
+    pub verified: bool,
+    pub share: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MetadataCollection {
+    pub verified: bool,
+    pub key: Pubkey,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UseMethod {
+    Burn,
+    Multiple,
+    Single,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MetadataUses {
+    pub use_method: UseMethod,
+    pub remaining: u64,
+    pub total: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProgrammableConfig {
+    /// Token Auth Rules rule set enforced on transfers, if any.
+    pub rule_set: Option<Pubkey>,
+}
+
+/// Every field of a Token Metadata account, including the ones added after
+/// `mpl-token-metadata` 1.4 such as `programmable_config`.
+///
+/// Strings have their NUL padding trimmed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenMetadata {
+    pub update_authority: Pubkey,
+    pub mint: Pubkey,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub seller_fee_basis_points: u16,
+    pub creators: Option<Vec<MetadataCreator>>,
+    pub primary_sale_happened: bool,
+    pub is_mutable: bool,
+    pub edition_nonce: Option<u8>,
+    pub token_standard: Option<TokenStandard>,
+    pub collection: Option<MetadataCollection>,
+    pub uses: Option<MetadataUses>,
+    /// Number of items of a sized collection.
+    pub collection_size: Option<u64>,
+    pub programmable_config: Option<ProgrammableConfig>,
+}
+
+// Decode -------------------------------------
+
+/// Walks Borsh-encoded bytes field by field.
+pub(crate) struct BorshReader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> BorshReader<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        Self { data, offset: 0 }
+    }
+
+    /// `len` often comes from an on-chain length prefix, so it may overflow the offset.
+    pub(crate) fn bytes(&mut self, len: usize) -> Web3WasmResult<&'a [u8]> {
+        let end = self.offset.checked_add(len);
+        match end.and_then(|end| self.data.get(self.offset..end)) {
+            Some(bytes) => {
+                self.offset += len;
+                Ok(bytes)
+            }
+            None => Err(Web3WasmError::Decode(format!(
+                "Unexpected end of data at offset {}",
+                self.offset
+            ))),
+        }
+    }
+
+    pub(crate) fn u8(&mut self) -> Web3WasmResult<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    pub(crate) fn bool(&mut self) -> Web3WasmResult<bool> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            value => Err(Web3WasmError::Decode(format!("Invalid bool {value}"))),
+        }
+    }
+
+    pub(crate) fn u16(&mut self) -> Web3WasmResult<u16> {
+        Ok(u16::from_le_bytes(self.bytes(2)?.try_into().unwrap()))
+    }
+
+    pub(crate) fn u32(&mut self) -> Web3WasmResult<u32> {
+        Ok(u32::from_le_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    pub(crate) fn u64(&mut self) -> Web3WasmResult<u64> {
+        Ok(u64::from_le_bytes(self.bytes(8)?.try_into().unwrap()))
+    }
+
+    pub(crate) fn pubkey(&mut self) -> Web3WasmResult<Pubkey> {
+        Ok(Pubkey::new_from_array(self.bytes(32)?.try_into().unwrap()))
+    }
+
+    pub(crate) fn string(&mut self) -> Web3WasmResult<String> {
+        let len = self.u32()? as usize;
+        let bytes = self.bytes(len)?;
+        Ok(String::from_utf8_lossy(bytes)
+            .trim_matches(char::from(0))
+            .to_owned())
+    }
+
+    pub(crate) fn option<T>(
+        &mut self,
+        read: impl FnOnce(&mut Self) -> Web3WasmResult<T>,
+    ) -> Web3WasmResult<Option<T>> {
+        match self.u8()? {
+            0 => Ok(None),
+            1 => Ok(Some(read(self)?)),
+            tag => Err(Web3WasmError::Decode(format!("Invalid option tag {tag}"))),
+        }
+    }
+
+    pub(crate) fn vec<T>(
+        &mut self,
+        mut read: impl FnMut(&mut Self) -> Web3WasmResult<T>,
+    ) -> Web3WasmResult<Vec<T>> {
+        let len = self.u32()?;
+        (0..len).map(|_| read(self)).collect()
+    }
+}
+
+/// `Key::MetadataV1`.
+const METADATA_KEY: u8 = 4;
+
+fn token_standard(reader: &mut BorshReader) -> Web3WasmResult<TokenStandard> {
+    Ok(match reader.u8()? {
+        0 => TokenStandard::NonFungible,
+        1 => TokenStandard::FungibleAsset,
+        2 => TokenStandard::Fungible,
+        3 => TokenStandard::NonFungibleEdition,
+        4 => TokenStandard::ProgrammableNonFungible,
+        5 => TokenStandard::ProgrammableNonFungibleEdition,
+        value => {
+            return Err(Web3WasmError::Decode(format!(
+                "Invalid token standard {value}"
+            )))
+        }
+    })
+}
+
+fn uses(reader: &mut BorshReader) -> Web3WasmResult<MetadataUses> {
+    let use_method = match reader.u8()? {
+        0 => UseMethod::Burn,
+        1 => UseMethod::Multiple,
+        2 => UseMethod::Single,
+        value => return Err(Web3WasmError::Decode(format!("Invalid use method {value}"))),
+    };
+    Ok(MetadataUses {
+        use_method,
+        remaining: reader.u64()?,
+        total: reader.u64()?,
+    })
+}
+
+/// Both enums only have a `V1` variant so far.
+fn v1<'a, T>(
+    reader: &mut BorshReader<'a>,
+    read: impl FnOnce(&mut BorshReader<'a>) -> Web3WasmResult<T>,
+) -> Web3WasmResult<T> {
+    match reader.u8()? {
+        0 => read(reader),
+        value => Err(Web3WasmError::Decode(format!("Unknown version {value}"))),
+    }
+}
+
+fn read_optional_fields(
+    reader: &mut BorshReader,
+    metadata: &mut TokenMetadata,
+) -> Web3WasmResult<()> {
+    metadata.edition_nonce = reader.option(BorshReader::u8)?;
+    metadata.token_standard = reader.option(token_standard)?;
+    metadata.collection = reader.option(|reader| {
+        Ok(MetadataCollection {
+            verified: reader.bool()?,
+            key: reader.pubkey()?,
+        })
+    })?;
+    metadata.uses = reader.option(uses)?;
+    metadata.collection_size = reader.option(|reader| v1(reader, BorshReader::u64))?;
+    metadata.programmable_config = reader.option(|reader| {
+        v1(reader, |reader| {
+            Ok(ProgrammableConfig {
+                rule_set: reader.option(BorshReader::pubkey)?,
+            })
+        })
+    })?;
+    Ok(())
+}
+
+/// Asset class of a mint without `token_standard`.
+///
+/// Mints with 0 decimals are `NonFungible` when their supply is 1 or unknown,
+/// and `FungibleAsset` otherwise.
+pub fn guess_asset_class(decimals: u8, supply: Option<u64>) -> AssetClass {
+    match (decimals, supply) {
+        (0, Some(1) | None) => AssetClass::NonFungible,
+        (0, Some(_)) => AssetClass::FungibleAsset,
+        _ => AssetClass::Fungible,
+    }
+}
+
+impl TokenMetadata {
+    /// Decodes a metadata account.
+    ///
+    /// Fields after `is_mutable` were added over time: accounts that end early or hold
+    /// garbage there, as some old ones do, get `None` from the first unreadable field on.
+    pub fn from_account_data(data: &[u8]) -> Web3WasmResult<Self> {
+        let mut reader = BorshReader::new(data);
+        let key = reader.u8()?;
+        if key != METADATA_KEY {
+            return Err(Web3WasmError::Decode(format!(
+                "Not a metadata account, key {key}"
+            )));
+        }
+
+        let mut metadata = TokenMetadata {
+            update_authority: reader.pubkey()?,
+            mint: reader.pubkey()?,
+            name: reader.string()?,
+            symbol: reader.string()?,
+            uri: reader.string()?,
+            seller_fee_basis_points: reader.u16()?,
+            creators: reader.option(|reader| {
+                reader.vec(|reader| {
+                    Ok(MetadataCreator {
+                        address: reader.pubkey()?,
+                        verified: reader.bool()?,
+                        share: reader.u8()?,
+                    })
+                })
+            })?,
+            primary_sale_happened: reader.bool()?,
+            is_mutable: reader.bool()?,
+            edition_nonce: None,
+            token_standard: None,
+            collection: None,
+            uses: None,
+            collection_size: None,
+            programmable_config: None,
+        };
+
+        // Keeps the fields read before the first one that fails.
+        read_optional_fields(&mut reader, &mut metadata).ok();
+
+        Ok(metadata)
+    }
+
+    /// `token_standard` when set, otherwise [`guess_asset_class`] from the mint.
+    pub fn asset_class(&self, decimals: u8, supply: Option<u64>) -> AssetClass {
+        match self.token_standard {
+            Some(token_standard) => token_standard.into(),
+            None => guess_asset_class(decimals, supply),
+        }
+    }
+
+    pub fn is_collection_verified(&self) -> bool {
+        matches!(
+            self.collection,
+            Some(MetadataCollection { verified: true, .. })
+        )
+    }
+}
+
+// Fetch -------------------------------------
+
+/// Full metadata of every mint, in the order of `mints`.
+///
+/// Unlike [`get_multiple_token_metadata`](crate::core::metaplex::get_multiple_token_metadata)
+/// this also decodes metadata written by newer program versions, e.g. programmable NFTs.
+pub async fn get_multiple_token_metadata_details(
+    client: &impl RpcClient,
+    mints: &[Pubkey],
+) -> Web3WasmResult<Vec<AccountResult<TokenMetadata>>> {
+    let metadata_keys = mints
+        .iter()
+        .map(|mint| find_metadata_account(mint).0)
+        .collect::<Vec<_>>();
+
+    let metadata_accounts = get_multiple_accounts_chunked(client, &metadata_keys).await?;
+    Ok(metadata_accounts
+        .into_iter()
+        .map(|account| match account {
+            Some(account) => TokenMetadata::from_account_data(&account.data).into(),
+            None => AccountResult::Missing,
+        })
+        .collect())
+}
+
+/// [`get_multiple_token_metadata_details`] keyed by mint address.
+pub async fn get_mint_metadata_details_map(
+    client: &impl RpcClient,
+    mints: &[Pubkey],
+) -> Web3WasmResult<HashMap<String, AccountResult<TokenMetadata>>> {
+    let metadata = get_multiple_token_metadata_details(client, mints).await?;
+    Ok(mints
+        .iter()
+        .map(|mint| mint.to_string())
+        .zip(metadata)
+        .collect())
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod test {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::tests::fixtures::fixture_client;
+
+    /// Metadata account of a programmable NFT in a verified collection.
+    fn pnft_metadata() -> Vec<u8> {
+        fn string(data: &mut Vec<u8>, value: &str, len: usize) {
+            data.extend((len as u32).to_le_bytes());
+            let mut bytes = value.as_bytes().to_vec();
+            bytes.resize(len, 0);
+            data.extend(bytes);
+        }
+
+        let mut data = vec![METADATA_KEY];
+        data.extend([1; 32]); // update_authority
+        data.extend([2; 32]); // mint
+        string(&mut data, "Mad Lad #1", 32);
+        string(&mut data, "MAD", 10);
+        string(&mut data, "https://example.com/1.json", 200);
+        data.extend(500u16.to_le_bytes());
+        data.push(1); // creators
+        data.extend(1u32.to_le_bytes());
+        data.extend([3; 32]);
+        data.extend([1, 100]);
+        data.extend([1, 0]); // primary_sale_happened, is_mutable
+        data.extend([1, 254]); // edition_nonce
+        data.extend([1, 4]); // token_standard
+        data.extend([1, 1]); // collection
+        data.extend([4; 32]);
+        data.push(0); // uses
+        data.push(0); // collection_details
+        data.extend([1, 0, 1]); // programmable_config
+        data.extend([5; 32]);
+        // Unused space at the end of the account.
+        data.extend([0; 64]);
+        data
+    }
+
+    #[test]
+    fn test_from_account_data() {
+        let metadata = TokenMetadata::from_account_data(&pnft_metadata()).unwrap();
+
+        assert_eq!(metadata.mint, Pubkey::new_from_array([2; 32]));
+        assert_eq!(metadata.name, "Mad Lad #1");
+        assert_eq!(metadata.symbol, "MAD");
+        assert_eq!(metadata.seller_fee_basis_points, 500);
+        assert_eq!(metadata.creators.as_ref().unwrap()[0].share, 100);
+        assert!(metadata.primary_sale_happened);
+        assert!(!metadata.is_mutable);
+        assert_eq!(metadata.edition_nonce, Some(254));
+        assert_eq!(
+            metadata.token_standard,
+            Some(TokenStandard::ProgrammableNonFungible)
+        );
+        assert!(metadata.is_collection_verified());
+        assert_eq!(metadata.uses, None);
+        assert_eq!(
+            metadata.programmable_config,
+            Some(ProgrammableConfig {
+                rule_set: Some(Pubkey::new_from_array([5; 32]))
+            })
+        );
+        assert_eq!(
+            metadata.asset_class(0, Some(1)),
+            AssetClass::ProgrammableNonFungible
+        );
+    }
+
+    #[test]
+    fn test_from_account_data_tolerates_old_accounts() {
+        let mut data = pnft_metadata();
+        // Ends right after `is_mutable`, as the very first metadata accounts do.
+        let end = 1 + 32 + 32 + (4 + 32) + (4 + 10) + (4 + 200) + 2 + (1 + 4 + 34) + 2;
+        data.truncate(end);
+        data.extend([1, 7, 9]); // edition_nonce then a corrupted token_standard
+
+        let metadata = TokenMetadata::from_account_data(&data).unwrap();
+        assert_eq!(metadata.edition_nonce, Some(7));
+        assert_eq!(metadata.token_standard, None);
+        assert_eq!(metadata.asset_class(0, None), AssetClass::NonFungible);
+        assert_eq!(
+            metadata.asset_class(0, Some(1_000)),
+            AssetClass::FungibleAsset
+        );
+        assert_eq!(metadata.asset_class(6, None), AssetClass::Fungible);
+
+        assert!(TokenMetadata::from_account_data(&[1, 2, 3]).is_err());
+
+        // A length prefix running past `usize::MAX` fails instead of overflowing.
+        let mut reader = BorshReader::new(&[4, 0, 0, 0, 0]);
+        reader.u8().unwrap();
+        assert_eq!(reader.bytes(usize::MAX).unwrap_err().code(), "DECODE_ERROR");
+    }
+
+    #[tokio::test]
+    async fn test_get_multiple_token_metadata_details_offline() {
+        let client = fixture_client("metadata");
+        let mint = Pubkey::from_str("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v").unwrap();
+        let metadata = get_multiple_token_metadata_details(&client, &[mint])
+            .await
+            .unwrap();
+
+        let metadata = metadata[0].as_found().unwrap();
+        assert_eq!(metadata.mint, mint);
+        assert_eq!(metadata.symbol, "USDC");
+        assert_eq!(metadata.asset_class(6, None), AssetClass::Fungible);
+    }
+}
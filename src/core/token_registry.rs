@@ -0,0 +1,340 @@
+use std::{collections::HashMap, str::FromStr, sync::OnceLock};
+
+use serde::Deserialize;
+use serde_json::Value;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::core::{
+    client::ClusterId,
+    error::{Web3WasmError, Web3WasmResult},
+};
+
+/// Tokens we ship with, in token-list format.
+const BUILT_IN_TOKENS: &str = r#"{
+  "name": "solana-web3-wasm",
+  "tokens": [
+    {
+      "chainId": 101,
+      "address": "So11111111111111111111111111111111111111112",
+      "symbol": "SOL",
+      "name": "Wrapped SOL",
+      "decimals": 9,
+      "logoURI": "https://raw.githubusercontent.com/solana-labs/token-list/main/assets/mainnet/So11111111111111111111111111111111111111112/logo.png"
+    },
+    {
+      "chainId": 101,
+      "address": "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+      "symbol": "USDC",
+      "name": "USD Coin",
+      "decimals": 6,
+      "logoURI": "https://raw.githubusercontent.com/solana-labs/token-list/main/assets/mainnet/EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v/logo.png",
+      "tags": ["stablecoin"]
+    },
+    {
+      "chainId": 101,
+      "address": "EsM2FadUJFzVxtzdekL7VThiYSfGrCNcvVqzNLiW4J8a",
+      "logoURI": "https://76ejs4snpuheojjadpqx3uvjvwu6ubbo6zyoni7czu3v3o3tei7q.arweave.net/_4iZck19DkclIBvhfdKpranqBC72cOaj4s03XbtzIj8?ext=jpg"
+    }
+  ]
+}"#;
+
+/// Raydium's icon for `mint_address`, which does not exist for most tokens.
+pub fn raydium_logo_url(mint_address: &str) -> String {
+    format!("https://img.raydium.io/icon/{mint_address}.png")
+}
+
+/// `chainId` of token-list entries.
+fn cluster_from_chain_id(chain_id: u64) -> Option<ClusterId> {
+    match chain_id {
+        101 => Some(ClusterId::Mainnet),
+        102 => Some(ClusterId::Testnet),
+        103 => Some(ClusterId::Devnet),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenEntry {
+    pub mint: Pubkey,
+    pub cluster: ClusterId,
+    pub symbol: String,
+    pub name: String,
+    pub decimals: Option<u8>,
+    pub logo: Option<String>,
+    pub tags: Vec<String>,
+    /// Listed by a trusted source, or tagged `verified` or `strict` by Jupiter.
+    pub verified: bool,
+}
+
+/// One token-list or Jupiter entry, every field but the address optional.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawToken {
+    address: String,
+    chain_id: Option<u64>,
+    #[serde(default)]
+    symbol: String,
+    #[serde(default)]
+    name: String,
+    decimals: Option<u8>,
+    #[serde(rename = "logoURI")]
+    logo_uri: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+/// A list of tokens, e.g. the Solana token-list or Jupiter's strict list.
+#[derive(Debug, Clone)]
+pub struct TokenSource {
+    pub name: String,
+    /// Higher wins when several sources know a mint.
+    pub priority: i32,
+    entries: HashMap<(ClusterId, Pubkey), TokenEntry>,
+}
+
+impl TokenSource {
+    /// Parses a token-list document (`{ "tokens": [...] }`) or a Jupiter array.
+    ///
+    /// Entries without `chainId`, as Jupiter's, are mainnet. Entries with an unknown
+    /// chain or an invalid address are skipped. `trusted` marks every entry verified.
+    pub fn from_json_slice(
+        name: impl Into<String>,
+        priority: i32,
+        trusted: bool,
+        json: &[u8],
+    ) -> Web3WasmResult<Self> {
+        let document: Value = serde_json::from_slice(json)?;
+        let tokens = match document {
+            Value::Array(tokens) => tokens,
+            Value::Object(mut list) => match list.remove("tokens") {
+                Some(Value::Array(tokens)) => tokens,
+                _ => {
+                    return Err(Web3WasmError::Decode(
+                        "Token list without a tokens array".to_owned(),
+                    ))
+                }
+            },
+            _ => {
+                return Err(Web3WasmError::Decode(
+                    "Token list is neither an array nor an object".to_owned(),
+                ))
+            }
+        };
+
+        let entries = tokens
+            .into_iter()
+            .filter_map(|token| serde_json::from_value::<RawToken>(token).ok())
+            .filter_map(|token| {
+                let cluster = match token.chain_id {
+                    Some(chain_id) => cluster_from_chain_id(chain_id)?,
+                    None => ClusterId::Mainnet,
+                };
+                let mint = Pubkey::from_str(&token.address).ok()?;
+                let verified = trusted
+                    || token
+                        .tags
+                        .iter()
+                        .any(|tag| tag == "verified" || tag == "strict");
+                let entry = TokenEntry {
+                    mint,
+                    cluster,
+                    symbol: token.symbol,
+                    name: token.name,
+                    decimals: token.decimals,
+                    logo: token.logo_uri.filter(|logo| !logo.is_empty()),
+                    tags: token.tags,
+                    verified,
+                };
+                Some(((cluster, mint), entry))
+            })
+            .collect();
+
+        Ok(Self {
+            name: name.into(),
+            priority,
+            entries,
+        })
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_file(
+        name: impl Into<String>,
+        priority: i32,
+        trusted: bool,
+        path: impl AsRef<std::path::Path>,
+    ) -> Web3WasmResult<Self> {
+        let json = std::fs::read(path).map_err(Web3WasmError::invalid_input)?;
+        Self::from_json_slice(name, priority, trusted, &json)
+    }
+
+    /// The tokens this crate ships with, lowest priority.
+    pub fn built_in() -> Self {
+        Self::from_json_slice("built-in", i32::MIN, true, BUILT_IN_TOKENS.as_bytes())
+            .expect("built-in token list is valid")
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&self, cluster: ClusterId, mint: &Pubkey) -> Option<&TokenEntry> {
+        self.entries.get(&(cluster, *mint))
+    }
+}
+
+/// Token lookups across sources, by priority.
+#[derive(Debug, Clone, Default)]
+pub struct TokenRegistry {
+    sources: Vec<TokenSource>,
+}
+
+impl TokenRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only the built-in source.
+    pub fn built_in() -> Self {
+        Self::new().with_source(TokenSource::built_in())
+    }
+
+    /// [`TokenRegistry::built_in`], parsed on first use and shared after that.
+    pub fn built_in_ref() -> &'static Self {
+        static BUILT_IN: OnceLock<TokenRegistry> = OnceLock::new();
+        BUILT_IN.get_or_init(Self::built_in)
+    }
+
+    /// Adds a source, ahead of sources with a lower priority and behind equal ones.
+    pub fn with_source(mut self, source: TokenSource) -> Self {
+        let index = self
+            .sources
+            .iter()
+            .position(|existing| existing.priority < source.priority)
+            .unwrap_or(self.sources.len());
+        self.sources.insert(index, source);
+        self
+    }
+
+    pub fn sources(&self) -> &[TokenSource] {
+        &self.sources
+    }
+
+    /// The entry of the highest priority source knowing `mint`.
+    ///
+    /// A missing logo is taken from the next source that has one, and the entry is
+    /// verified when any source says so.
+    pub fn get(&self, cluster: ClusterId, mint: &Pubkey) -> Option<TokenEntry> {
+        let mut entries = self
+            .sources
+            .iter()
+            .filter_map(|source| source.get(cluster, mint));
+        let mut token = entries.next()?.clone();
+        for entry in entries {
+            token.verified |= entry.verified;
+            if token.logo.is_none() {
+                token.logo = entry.logo.clone();
+            }
+        }
+        Some(token)
+    }
+
+    /// Registry logo, or Raydium's guess when no source has one.
+    pub fn logo(&self, cluster: ClusterId, mint_address: &str) -> String {
+        Pubkey::from_str(mint_address)
+            .ok()
+            .and_then(|mint| self.get(cluster, &mint))
+            .and_then(|token| token.logo)
+            .unwrap_or_else(|| raydium_logo_url(mint_address))
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod test {
+    use super::*;
+
+    const USDC: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+    const BONK: &str = "DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263";
+
+    fn jupiter() -> TokenSource {
+        let json = format!(
+            r#"[
+              {{ "address": "{BONK}", "symbol": "Bonk", "name": "Bonk", "decimals": 5,
+                 "logoURI": "https://arweave.net/bonk", "tags": ["community", "strict"] }},
+              {{ "address": "{USDC}", "symbol": "USDC", "name": "USD Coin (Jupiter)", "decimals": 6,
+                 "logoURI": "", "tags": [] }},
+              {{ "address": "not a pubkey", "symbol": "BAD" }},
+              {{ "symbol": "NOADDRESS" }}
+            ]"#
+        );
+        TokenSource::from_json_slice("jupiter", 10, false, json.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_token_source_formats() {
+        let built_in = TokenSource::built_in();
+        assert_eq!(built_in.len(), 3);
+        assert!(std::ptr::eq(
+            TokenRegistry::built_in_ref(),
+            TokenRegistry::built_in_ref()
+        ));
+
+        let token_list = format!(
+            r#"{{ "tokens": [
+              {{ "chainId": 103, "address": "{USDC}", "symbol": "USDC-DEV", "name": "Devnet USDC" }},
+              {{ "chainId": 999, "address": "{BONK}", "symbol": "OTHER" }}
+            ] }}"#
+        );
+        let devnet =
+            TokenSource::from_json_slice("devnet", 0, true, token_list.as_bytes()).unwrap();
+        assert_eq!(devnet.len(), 1);
+        let usdc = USDC.parse().unwrap();
+        assert_eq!(
+            devnet.get(ClusterId::Devnet, &usdc).unwrap().symbol,
+            "USDC-DEV"
+        );
+        assert!(devnet.get(ClusterId::Mainnet, &usdc).is_none());
+
+        let jupiter = jupiter();
+        assert_eq!(jupiter.len(), 2);
+        assert!(
+            jupiter
+                .get(ClusterId::Mainnet, &BONK.parse().unwrap())
+                .unwrap()
+                .verified
+        );
+
+        assert!(TokenSource::from_json_slice("bad", 0, false, b"{}").is_err());
+    }
+
+    #[test]
+    fn test_token_registry_priority() {
+        let registry = TokenRegistry::built_in().with_source(jupiter());
+        assert_eq!(registry.sources()[0].name, "jupiter");
+
+        // Jupiter wins, but has no logo and is not verified: the built-in list fills both.
+        let usdc = registry
+            .get(ClusterId::Mainnet, &USDC.parse().unwrap())
+            .unwrap();
+        assert_eq!(usdc.name, "USD Coin (Jupiter)");
+        assert_eq!(usdc.tags, Vec::<String>::new());
+        assert!(usdc.verified);
+        assert!(usdc.logo.unwrap().contains("token-list"));
+
+        assert_eq!(
+            registry.logo(ClusterId::Mainnet, BONK),
+            "https://arweave.net/bonk"
+        );
+        assert_eq!(
+            registry.logo(ClusterId::Devnet, BONK),
+            raydium_logo_url(BONK)
+        );
+        assert!(registry
+            .get(ClusterId::Devnet, &USDC.parse().unwrap())
+            .is_none());
+    }
+}
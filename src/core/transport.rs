@@ -0,0 +1,331 @@
+use std::{future::Future, sync::Mutex, time::Duration};
+
+use async_trait::async_trait;
+use fluvio_wasm_timer::Delay;
+use futures::future::{select, Either};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::core::{
+    client::ClientConfig,
+    error::{RpcError, Web3WasmError, Web3WasmResult},
+};
+
+/// Carries one JSON-RPC request body to a node and returns the response body.
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait RpcTransport: Send + Sync {
+    async fn send(&self, request: &Value) -> Web3WasmResult<Value>;
+}
+
+/// HTTP transport honouring the `ClientConfig` timeout, headers and bearer token.
+pub struct HttpTransport {
+    url: String,
+    headers: Vec<(String, String)>,
+    timeout: Option<Duration>,
+    http: reqwest::Client,
+}
+
+impl HttpTransport {
+    pub fn new(config: &ClientConfig) -> Self {
+        Self {
+            url: config.url(),
+            headers: config.http_headers(),
+            timeout: config.timeout,
+            http: reqwest::Client::new(),
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl RpcTransport for HttpTransport {
+    async fn send(&self, request: &Value) -> Web3WasmResult<Value> {
+        let mut builder = self.http.post(&self.url).json(request);
+        for (name, value) in &self.headers {
+            builder = builder.header(name, value);
+        }
+
+        with_timeout(
+            async {
+                let response = builder
+                    .send()
+                    .await
+                    .map_err(|err| RpcError::Transport(err.to_string()))?;
+                let status = response.status();
+                if !status.is_success() {
+                    let retry_after = response
+                        .headers()
+                        .get("retry-after")
+                        .and_then(|value| value.to_str().ok())
+                        .and_then(|value| value.trim().parse::<u64>().ok())
+                        .map(Duration::from_secs);
+                    return Err(RpcError::HttpStatus {
+                        status: status.as_u16(),
+                        retry_after,
+                        body: response.text().await.unwrap_or_default(),
+                    }
+                    .into());
+                }
+                response
+                    .json::<Value>()
+                    .await
+                    .map_err(Web3WasmError::decode)
+            },
+            self.timeout,
+        )
+        .await
+    }
+}
+
+pub(crate) async fn with_timeout<T>(
+    future: impl Future<Output = Web3WasmResult<T>>,
+    timeout: Option<Duration>,
+) -> Web3WasmResult<T> {
+    let timeout = match timeout {
+        Some(timeout) => timeout,
+        None => return future.await,
+    };
+
+    match select(Box::pin(future), Delay::new(timeout)).await {
+        Either::Left((result, _)) => result,
+        Either::Right(_) => Err(RpcError::Timeout(timeout).into()),
+    }
+}
+
+/// One canned exchange, as stored in a fixture file.
+///
+/// Fixtures without `params` match every request to `method`, and `null`
+/// inside `params` matches any value, e.g. `[null, { "encoding": "jsonParsed" }]`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RpcFixture {
+    pub method: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub params: Option<Value>,
+    pub response: Value,
+}
+
+impl RpcFixture {
+    fn matches(&self, method: &str, params: &Value) -> bool {
+        self.method == method
+            && match &self.params {
+                Some(pattern) => json_matches(pattern, params),
+                None => true,
+            }
+    }
+}
+
+fn json_matches(pattern: &Value, value: &Value) -> bool {
+    match (pattern, value) {
+        (Value::Null, _) => true,
+        (Value::Array(pattern), Value::Array(value)) => {
+            pattern.len() == value.len()
+                && pattern.iter().zip(value).all(|(p, v)| json_matches(p, v))
+        }
+        (Value::Object(pattern), Value::Object(value)) => pattern
+            .iter()
+            .all(|(key, p)| value.get(key).map_or(false, |v| json_matches(p, v))),
+        (pattern, value) => pattern == value,
+    }
+}
+
+/// Replays canned JSON-RPC responses, no network involved.
+///
+/// Fixtures with `params` win over method-only ones.
+#[derive(Debug, Default)]
+pub struct MockTransport {
+    fixtures: Vec<RpcFixture>,
+}
+
+impl MockTransport {
+    pub fn new(fixtures: Vec<RpcFixture>) -> Self {
+        Self { fixtures }
+    }
+
+    pub fn from_json_str(json: &str) -> Web3WasmResult<Self> {
+        Ok(Self::new(serde_json::from_str(json)?))
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_file(path: impl AsRef<std::path::Path>) -> Web3WasmResult<Self> {
+        Self::from_json_str(&std::fs::read_to_string(path).map_err(Web3WasmError::invalid_input)?)
+    }
+
+    /// Answers `method` with `result`, whatever the params.
+    pub fn with_result(mut self, method: &str, result: Value) -> Self {
+        self.fixtures.push(RpcFixture {
+            method: method.to_owned(),
+            params: None,
+            response: json!({ "jsonrpc": "2.0", "result": result }),
+        });
+        self
+    }
+
+    pub fn fixtures(&self) -> &[RpcFixture] {
+        &self.fixtures
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl RpcTransport for MockTransport {
+    async fn send(&self, request: &Value) -> Web3WasmResult<Value> {
+        let method = request["method"].as_str().unwrap_or_default();
+        let params = &request["params"];
+
+        let fixture = self
+            .fixtures
+            .iter()
+            .find(|f| f.params.is_some() && f.matches(method, params))
+            .or_else(|| self.fixtures.iter().find(|f| f.matches(method, params)));
+
+        match fixture {
+            Some(fixture) => {
+                let mut response = fixture.response.clone();
+                response["id"] = request["id"].clone();
+                Ok(response)
+            }
+            None => Err(Web3WasmError::NotFound(format!(
+                "No fixture for {} {}",
+                method, params
+            ))),
+        }
+    }
+}
+
+/// Forwards to an inner transport and records every exchange as a fixture.
+pub struct RecordingTransport<T> {
+    inner: T,
+    recorded: Mutex<Vec<RpcFixture>>,
+}
+
+impl<T: RpcTransport> RecordingTransport<T> {
+    pub fn new(inner: T) -> Self {
+        Self {
+            inner,
+            recorded: Mutex::new(vec![]),
+        }
+    }
+
+    pub fn fixtures(&self) -> Vec<RpcFixture> {
+        self.recorded.lock().unwrap().clone()
+    }
+
+    pub fn to_json_string(&self) -> Web3WasmResult<String> {
+        Ok(serde_json::to_string_pretty(&self.fixtures())?)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save(&self, path: impl AsRef<std::path::Path>) -> Web3WasmResult<()> {
+        std::fs::write(path, self.to_json_string()?).map_err(Web3WasmError::invalid_input)
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl<T: RpcTransport> RpcTransport for RecordingTransport<T> {
+    async fn send(&self, request: &Value) -> Web3WasmResult<Value> {
+        let mut response = self.inner.send(request).await?;
+        if let Some(response) = response.as_object_mut() {
+            response.remove("id");
+        }
+
+        self.recorded.lock().unwrap().push(RpcFixture {
+            method: request["method"].as_str().unwrap_or_default().to_owned(),
+            params: Some(request["params"].clone()),
+            response: response.clone(),
+        });
+
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod test {
+    use super::*;
+
+    fn request(method: &str, params: Value) -> Value {
+        json!({ "jsonrpc": "2.0", "id": 7, "method": method, "params": params })
+    }
+
+    #[tokio::test]
+    async fn test_mock_transport_prefers_exact_params() {
+        let transport = MockTransport::from_json_str(
+            r#"[
+                { "method": "getSlot", "response": { "jsonrpc": "2.0", "result": 1 } },
+                { "method": "getSlot", "params": [{ "commitment": "finalized" }], "response": { "jsonrpc": "2.0", "result": 2 } }
+            ]"#,
+        )
+        .unwrap();
+
+        let any = transport
+            .send(&request("getSlot", json!([{ "commitment": "confirmed" }])))
+            .await
+            .unwrap();
+        let exact = transport
+            .send(&request("getSlot", json!([{ "commitment": "finalized" }])))
+            .await
+            .unwrap();
+
+        assert_eq!(any, json!({ "jsonrpc": "2.0", "result": 1, "id": 7 }));
+        assert_eq!(exact, json!({ "jsonrpc": "2.0", "result": 2, "id": 7 }));
+    }
+
+    #[tokio::test]
+    async fn test_mock_transport_params_wildcard() {
+        let transport = MockTransport::from_json_str(
+            r#"[
+                { "method": "getMultipleAccounts", "params": [null, { "encoding": "jsonParsed" }], "response": { "jsonrpc": "2.0", "result": "parsed" } },
+                { "method": "getMultipleAccounts", "response": { "jsonrpc": "2.0", "result": "raw" } }
+            ]"#,
+        )
+        .unwrap();
+
+        let parsed = transport
+            .send(&request(
+                "getMultipleAccounts",
+                json!([["key"], { "encoding": "jsonParsed", "commitment": "confirmed" }]),
+            ))
+            .await
+            .unwrap();
+        let raw = transport
+            .send(&request(
+                "getMultipleAccounts",
+                json!([["key"], { "encoding": "base64", "commitment": "confirmed" }]),
+            ))
+            .await
+            .unwrap();
+
+        assert_eq!(parsed["result"], "parsed");
+        assert_eq!(raw["result"], "raw");
+    }
+
+    #[tokio::test]
+    async fn test_mock_transport_missing_fixture() {
+        let transport = MockTransport::default();
+        let err = transport
+            .send(&request("getHealth", json!([])))
+            .await
+            .unwrap_err();
+
+        assert_eq!(err.to_string(), "No fixture for getHealth []");
+    }
+
+    #[tokio::test]
+    async fn test_recording_transport_round_trip() {
+        let recorder =
+            RecordingTransport::new(MockTransport::default().with_result("getSlot", json!(42)));
+        recorder.send(&request("getSlot", json!([]))).await.unwrap();
+
+        let replay = MockTransport::from_json_str(&recorder.to_json_string().unwrap()).unwrap();
+        let response = replay.send(&request("getSlot", json!([]))).await.unwrap();
+
+        assert_eq!(response["result"], 42);
+        assert_eq!(replay.fixtures()[0].params, Some(json!([])));
+    }
+}
@@ -1,23 +1,42 @@
-use anyhow::{self, bail};
 use mpl_token_metadata::state::Metadata;
 use solana_extra_wasm::program::spl_associated_token_account::get_associated_token_address;
-use std::collections::HashMap;
+use std::{collections::HashMap, str::FromStr};
 
-use crate::core::{client::Web3WasmClient, metaplex::get_mint_metadata_map};
+use crate::core::{
+    account::get_multiple_token_accounts_chunked,
+    client::{ClientConfig, ClusterId, EndPoint, Web3WasmClient},
+    error::{Web3WasmError, Web3WasmResult},
+    json_rpc::JsonRpcClient,
+    metaplex::get_mint_metadata_map,
+    rpc::RpcClient,
+    token_metadata::{get_mint_metadata_details_map, TokenMetadata},
+};
 use solana_client_wasm::WasmClient;
 use solana_sdk::pubkey::Pubkey;
 
-pub struct NftInformation {
-    client: WasmClient,
+pub struct NftInformation<C = WasmClient> {
+    client: C,
+}
+
+impl NftInformation<JsonRpcClient> {
+    pub fn new_with_config(config: ClientConfig) -> Self {
+        Self {
+            client: JsonRpcClient::new(config),
+        }
+    }
 }
 
 impl NftInformation {
     pub fn new_from_str(cluster_str: &str) -> Result<Self, String> {
-        match cluster_str {
-            "mainnet" => Ok(Self::new_mainnet()),
-            "mainnet-beta" => Ok(Self::new_mainnet()),
-            "devnet" => Ok(Self::new_devnet()),
-            _ => Err(format!("Invalid cluster_str: {}", cluster_str)),
+        match ClusterId::from_str(cluster_str) {
+            Ok(cluster_id) => Ok(Self::new_from_cluster(cluster_id)),
+            Err(_) => Err(format!("Invalid cluster_str: {}", cluster_str)),
+        }
+    }
+
+    pub fn new_from_cluster(cluster_id: ClusterId) -> Self {
+        Self {
+            client: <WasmClient as Web3WasmClient>::new(&EndPoint::from(cluster_id)),
         }
     }
 
@@ -32,13 +51,19 @@ impl NftInformation {
             client: WasmClient::new_devnet(),
         }
     }
+}
 
-    // read
-    pub async fn find_nfts_by_mints(
-        self: NftInformation,
+impl<C: RpcClient> NftInformation<C> {
+    pub fn new_with_client(client: C) -> Self {
+        Self { client }
+    }
+
+    /// Fails with `NotFound` unless `owner_address` holds one of `mints` in its ATA.
+    async fn ensure_owns_any(
+        &self,
         owner_address: &Pubkey,
         mints: &[Pubkey],
-    ) -> anyhow::Result<HashMap<String, Metadata>> {
+    ) -> Web3WasmResult<()> {
         // 1. AST exist?
         let ast_list = mints
             .iter()
@@ -46,55 +71,102 @@ impl NftInformation {
             .collect::<Vec<_>>();
 
         // 2. Has NFT in that AST?
-        let mut token_balances = self
-            .client
-            .get_multiple_token_accounts(&ast_list)
+        let mut token_balances = get_multiple_token_accounts_chunked(&self.client, &ast_list)
             .await?
             .into_iter()
             .flatten()
             .filter(|e| e.token_amount.amount == "1");
 
         if token_balances.next().is_none() {
-            bail!("No token found.")
+            return Err(Web3WasmError::NotFound("No token found.".to_owned()));
         }
 
-        // 3. Get NFT info
-        let token_metadata_map = get_mint_metadata_map(&self.client, mints).await.unwrap();
+        Ok(())
+    }
+
+    // read
+    pub async fn find_nfts_by_mints(
+        self,
+        owner_address: &Pubkey,
+        mints: &[Pubkey],
+    ) -> Web3WasmResult<HashMap<String, Metadata>> {
+        self.ensure_owns_any(owner_address, mints).await?;
+
+        Ok(get_mint_metadata_map(&self.client, mints)
+            .await?
+            .into_iter()
+            .filter_map(|(mint, metadata)| Some((mint, metadata.found()?)))
+            .collect())
+    }
 
-        println!("token_metadata_map: {:#?}", token_metadata_map);
+    /// Like [`Self::find_nfts_by_mints`], with the full metadata: token standard,
+    /// collection, uses and programmable config.
+    pub async fn find_nft_details_by_mints(
+        self,
+        owner_address: &Pubkey,
+        mints: &[Pubkey],
+    ) -> Web3WasmResult<HashMap<String, TokenMetadata>> {
+        self.ensure_owns_any(owner_address, mints).await?;
 
-        Ok(token_metadata_map)
+        Ok(get_mint_metadata_details_map(&self.client, mints)
+            .await?
+            .into_iter()
+            .filter_map(|(mint, metadata)| Some((mint, metadata.found()?)))
+            .collect())
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::info::nft::NftInformation;
+    use crate::{
+        core::token_metadata::AssetClass, info::nft::NftInformation,
+        tests::fixtures::fixture_client,
+    };
     use solana_sdk::pubkey::Pubkey;
     use std::str::FromStr;
 
+    #[test]
+    fn test_new_from_str() {
+        assert!(NftInformation::new_from_str("mainnet").is_ok());
+        assert!(NftInformation::new_from_str("mainnet-beta").is_ok());
+        assert!(NftInformation::new_from_str("devnet").is_ok());
+        assert!(NftInformation::new_from_str("localnet").is_ok());
+        assert_eq!(
+            NftInformation::new_from_str("moon").err(),
+            Some("Invalid cluster_str: moon".to_owned())
+        );
+    }
+
     #[tokio::test]
     async fn test_find_nfts_by_mints() {
         let mint_address = "A2NzysADP3a6FzgKkh4dzQbwK6CgsJcdo3Rz6opfFMPy";
-        let nft_info = NftInformation::new_devnet();
+        let nft_info = NftInformation::new_with_client(fixture_client("nft"));
         let token_metadata_info = nft_info
             .find_nfts_by_mints(
-                // owner_address
                 &Pubkey::from_str("9K9RDUPvRfcVmHnoThUGkdR2bfQwa9oH1bs8RsmR2fjc").unwrap(),
-                // mints
                 &[Pubkey::from_str(mint_address).unwrap()],
             )
             .await
             .unwrap();
 
-        assert_eq!(
-            token_metadata_info
-                .get(mint_address)
-                .unwrap()
-                .mint
-                .to_string(),
-            mint_address.to_owned()
-        );
+        assert_eq!(token_metadata_info[mint_address].data.symbol, "AVA");
         assert_eq!(token_metadata_info.len(), 1);
     }
+
+    #[tokio::test]
+    async fn test_find_nft_details_by_mints() {
+        let mint_address = "A2NzysADP3a6FzgKkh4dzQbwK6CgsJcdo3Rz6opfFMPy";
+        let nft_info = NftInformation::new_with_client(fixture_client("nft"));
+        let details = nft_info
+            .find_nft_details_by_mints(
+                &Pubkey::from_str("9K9RDUPvRfcVmHnoThUGkdR2bfQwa9oH1bs8RsmR2fjc").unwrap(),
+                &[Pubkey::from_str(mint_address).unwrap()],
+            )
+            .await
+            .unwrap();
+
+        let metadata = &details[mint_address];
+        assert_eq!(metadata.symbol, "AVA");
+        assert_eq!(metadata.asset_class(0, Some(1)), AssetClass::NonFungible);
+    }
 }
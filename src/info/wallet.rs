@@ -1,11 +1,18 @@
 use crate::{
-    core::{metaplex::get_mint_metadata_map, mint::get_logo_by_mint_address},
-    solana_client_wasm::{utils::rpc_filter::TokenAccountsFilter, WasmClient},
+    core::{
+        account::AccountResult,
+        client::ClusterId,
+        error::{Web3WasmError, Web3WasmResult},
+        metadata_resolver::resolve_mint_metadata_map,
+        rpc::RpcClient,
+        token_metadata::guess_asset_class,
+        token_registry::TokenRegistry,
+    },
+    solana_client_wasm::utils::rpc_filter::TokenAccountsFilter,
     wallet::sort::sort_and_parse_token_accounts,
 };
-use anyhow::bail;
 use async_trait::async_trait;
-use solana_extra_wasm::program::spl_token;
+use solana_extra_wasm::program::{spl_token, spl_token_2022};
 use solana_sdk::{native_token::lamports_to_sol, pubkey::Pubkey};
 use std::str::FromStr;
 
@@ -14,34 +21,82 @@ use crate::wallet::structs::*;
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
 pub trait WalletInformation {
-    async fn get_wallet_info(&self, wallet_address: &str) -> Result<WalletInfo, anyhow::Error>;
+    /// [`Self::get_wallet_info_with_registry`] with the built-in mainnet tokens.
+    async fn get_wallet_info(&self, wallet_address: &str) -> Web3WasmResult<WalletInfo>;
+    /// [`Self::get_wallet_token_infos_with_registry`] with the built-in mainnet tokens.
     async fn get_wallet_token_infos(
         &self,
         wallet_address: &str,
         token_address: Option<String>,
-    ) -> Result<Vec<Option<WalletTokenInfo>>, anyhow::Error>;
+    ) -> Web3WasmResult<Vec<Option<WalletTokenInfo>>>;
+
+    /// SOL balance, with the wrapped SOL logo of `registry`.
+    async fn get_wallet_info_with_registry(
+        &self,
+        wallet_address: &str,
+        registry: &TokenRegistry,
+        cluster: ClusterId,
+    ) -> Web3WasmResult<WalletInfo>;
+
+    /// Token balances with their metadata, `registry` covering mints without any.
+    ///
+    /// Entries are `None` for mints neither metadata nor `registry` know.
+    async fn get_wallet_token_infos_with_registry(
+        &self,
+        wallet_address: &str,
+        token_address: Option<String>,
+        registry: &TokenRegistry,
+        cluster: ClusterId,
+    ) -> Web3WasmResult<Vec<Option<WalletTokenInfo>>>;
 }
 
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
-impl WalletInformation for WasmClient {
-    async fn get_wallet_info(&self, wallet_address: &str) -> Result<WalletInfo, anyhow::Error> {
+impl<C: RpcClient> WalletInformation for C {
+    async fn get_wallet_info(&self, wallet_address: &str) -> Web3WasmResult<WalletInfo> {
+        self.get_wallet_info_with_registry(
+            wallet_address,
+            TokenRegistry::built_in_ref(),
+            ClusterId::Mainnet,
+        )
+        .await
+    }
+
+    async fn get_wallet_token_infos(
+        &self,
+        wallet_address: &str,
+        token_address: Option<String>,
+    ) -> Web3WasmResult<Vec<Option<WalletTokenInfo>>> {
+        self.get_wallet_token_infos_with_registry(
+            wallet_address,
+            token_address,
+            TokenRegistry::built_in_ref(),
+            ClusterId::Mainnet,
+        )
+        .await
+    }
+
+    async fn get_wallet_info_with_registry(
+        &self,
+        wallet_address: &str,
+        registry: &TokenRegistry,
+        cluster: ClusterId,
+    ) -> Web3WasmResult<WalletInfo> {
         let account = self.get_account(&Pubkey::from_str(wallet_address)?).await;
 
         let decimals = 9u8;
         let symbol = "SOL".to_owned();
-        let logo = get_logo_by_mint_address("So11111111111111111111111111111111111111112");
+        let logo = registry.logo(cluster, "So11111111111111111111111111111111111111112");
         let name = "Solana Native Token".to_owned();
 
         match account {
             Ok(account) => {
-                let lamports_string = account.lamports.to_string();
-                let ui_balance = lamports_to_sol(lamports_string.parse::<u64>()?);
+                let ui_balance = lamports_to_sol(account.lamports);
 
                 Ok(WalletInfo {
                     decimals,
                     lamports: account.lamports,
-                    lamports_string,
+                    lamports_string: account.lamports.to_string(),
                     ui_balance,
                     ui_balance_string: ui_balance.to_string(),
                     symbol,
@@ -49,50 +104,61 @@ impl WalletInformation for WasmClient {
                     name,
                 })
             }
-            Err(err) => {
-                if err.to_string().starts_with("Client error:") {
-                    Ok(WalletInfo {
-                        decimals,
-                        lamports: 0u64,
-                        lamports_string: "0".to_string(),
-                        ui_balance: 0f64,
-                        ui_balance_string: "0".to_string(),
-                        symbol,
-                        logo,
-                        name,
-                    })
-                } else {
-                    bail!(err)
-                }
-            }
+            Err(Web3WasmError::NotFound(_)) => Ok(WalletInfo {
+                decimals,
+                lamports: 0u64,
+                lamports_string: "0".to_string(),
+                ui_balance: 0f64,
+                ui_balance_string: "0".to_string(),
+                symbol,
+                logo,
+                name,
+            }),
+            Err(err) => Err(err),
         }
     }
 
-    async fn get_wallet_token_infos(
+    async fn get_wallet_token_infos_with_registry(
         &self,
         wallet_address: &str,
         token_address: Option<String>,
-    ) -> Result<Vec<Option<WalletTokenInfo>>, anyhow::Error> {
+        registry: &TokenRegistry,
+        cluster: ClusterId,
+    ) -> Web3WasmResult<Vec<Option<WalletTokenInfo>>> {
         let owner = Pubkey::from_str(wallet_address)?;
         let token = match token_address {
             Some(token_address) => Some(Pubkey::from_str(&token_address)?),
             None => None,
         };
 
-        let token_account_filter = match token {
-            Some(token) => TokenAccountsFilter::Mint(token),
-            None => TokenAccountsFilter::ProgramId(spl_token::id()),
+        let accounts = match token {
+            Some(token) => {
+                self.get_token_accounts_by_owner(&owner, &TokenAccountsFilter::Mint(token))
+                    .await?
+            }
+            None => {
+                let (token_accounts, token_2022_accounts) = futures::try_join!(
+                    self.get_token_accounts_by_owner(
+                        &owner,
+                        &TokenAccountsFilter::ProgramId(spl_token::id())
+                    ),
+                    self.get_token_accounts_by_owner(
+                        &owner,
+                        &TokenAccountsFilter::ProgramId(spl_token_2022::id())
+                    ),
+                )?;
+                let mut accounts = token_accounts;
+                accounts.extend(token_2022_accounts);
+                accounts
+            }
         };
-        let accounts = self
-            .get_token_accounts_by_owner(&owner, token_account_filter)
-            .await?;
 
         if accounts.is_empty() {
             return Ok(vec![]);
         }
 
         let (mint_accounts, unsupported_accounts, max_len_balance, includes_aux) =
-            sort_and_parse_token_accounts(&owner, accounts, &spl_token::id());
+            sort_and_parse_token_accounts(&owner, accounts);
         let aux_len = if includes_aux { 10 } else { 0 };
 
         let wallet_token_accounts = WalletTokenAccounts {
@@ -115,7 +181,7 @@ impl WalletInformation for WasmClient {
             .map(|flatten_account| Pubkey::from_str(&flatten_account.account.mint).unwrap())
             .collect::<Vec<_>>();
 
-        let mint_metadata_map = get_mint_metadata_map(self, &mints).await?;
+        let mint_metadata_map = resolve_mint_metadata_map(self, &mints).await?;
 
         let wallet_token_info_list = flatten_accounts
             .into_iter()
@@ -125,23 +191,41 @@ impl WalletInformation for WasmClient {
                 let address = flatten_account.address.to_owned();
                 let mint = ui_token_account.mint.to_string();
 
-                // Handle unsupported mint
-                mint_metadata_map.get(&mint).map(|mint_metadata| {
-                    let symbol = mint_metadata.data.symbol.to_owned();
-                    let name = mint_metadata.data.name.to_owned();
-                    let logo = get_logo_by_mint_address(&mint);
-
-                    WalletTokenInfo {
-                        mint,
-                        name,
-                        symbol,
-                        logo,
-                        address,
-                        decimals: token_amount.decimals.to_owned(),
-                        amount_string: token_amount.amount.to_owned(),
-                        ui_amount: token_amount.ui_amount.unwrap_or(0.0).to_owned(),
-                        ui_amount_string: token_amount.ui_amount_string.to_owned(),
+                // Handle unsupported mint, or a broken metadata account
+                let mint_metadata = mint_metadata_map
+                    .get(&mint)
+                    .and_then(AccountResult::as_found);
+                // Supply is not known here, legacy 0 decimals mints count as NFTs.
+                let (symbol, name, asset_class) = match mint_metadata {
+                    Some(mint_metadata) => (
+                        mint_metadata.symbol.to_owned(),
+                        mint_metadata.name.to_owned(),
+                        mint_metadata.asset_class(token_amount.decimals, None),
+                    ),
+                    None => {
+                        let entry = registry
+                            .get(cluster, &Pubkey::from_str(&mint).ok()?)
+                            .filter(|entry| !entry.symbol.is_empty())?;
+                        (
+                            entry.symbol,
+                            entry.name,
+                            guess_asset_class(token_amount.decimals, None),
+                        )
                     }
+                };
+                let logo = registry.logo(cluster, &mint);
+
+                Some(WalletTokenInfo {
+                    mint,
+                    name,
+                    symbol,
+                    logo,
+                    address,
+                    decimals: token_amount.decimals.to_owned(),
+                    amount_string: token_amount.amount.to_owned(),
+                    ui_amount: token_amount.ui_amount.unwrap_or(0.0).to_owned(),
+                    ui_amount_string: token_amount.ui_amount_string.to_owned(),
+                    asset_class,
                 })
             })
             .collect::<Vec<_>>();
@@ -153,43 +237,45 @@ impl WalletInformation for WasmClient {
 #[cfg(not(target_arch = "wasm32"))]
 #[cfg(test)]
 mod test {
-    use {crate::core::client::Web3WasmClient, solana_client_wasm::WasmClient};
+    use crate::{core::token_metadata::AssetClass, tests::fixtures::fixture_client};
 
     use super::WalletInformation;
 
     #[tokio::test]
     async fn test_get_wallet_info() {
-        let pubkey = "DcJGXTE7L1XQtFSdvBv2NPkGCxQ1cziem1yXnqfy2rVy";
-        let wallet_info = WasmClient::new_mainnet()
-            .get_wallet_info(pubkey)
+        let client = fixture_client("wallet");
+        let wallet_info = client
+            .get_wallet_info("DcJGXTE7L1XQtFSdvBv2NPkGCxQ1cziem1yXnqfy2rVy")
             .await
             .unwrap();
 
-        println!("wallet_info: {wallet_info:#?}");
-        assert!(wallet_info.lamports > 0);
+        assert_eq!(wallet_info.lamports, 1895991);
+        assert_eq!(wallet_info.ui_balance_string, "0.001895991");
     }
 
     #[tokio::test]
     async fn test_get_wallet_info_not_exist() {
-        let pubkey = "3mPuPCgmdexxcSYtpKDTPktTnEYHJcsZxCJdfemN1xgt";
-        let wallet_info = WasmClient::new_mainnet()
-            .get_wallet_info(pubkey)
+        let client = fixture_client("wallet");
+        let wallet_info = client
+            .get_wallet_info("3mPuPCgmdexxcSYtpKDTPktTnEYHJcsZxCJdfemN1xgt")
             .await
             .unwrap();
 
-        println!("wallet_info: {wallet_info:#?}");
         assert!(wallet_info.lamports == 0);
     }
 
     #[tokio::test]
     async fn test_get_wallet_token_infos() {
-        let pubkey = "DcJGXTE7L1XQtFSdvBv2NPkGCxQ1cziem1yXnqfy2rVy";
-        let token_info = WasmClient::new_mainnet()
-            .get_wallet_token_infos(pubkey, None)
+        let client = fixture_client("wallet");
+        let token_info = client
+            .get_wallet_token_infos("DcJGXTE7L1XQtFSdvBv2NPkGCxQ1cziem1yXnqfy2rVy", None)
             .await
             .unwrap();
 
-        println!("token_info: {token_info:#?}");
-        assert!(!token_info.is_empty());
+        let usdc = token_info[0].as_ref().unwrap();
+        assert_eq!(usdc.symbol, "USDC");
+        assert_eq!(usdc.asset_class, AssetClass::Fungible);
+        assert_eq!(usdc.amount_string, "1895991");
+        assert_eq!(usdc.address, "B8MA5aWJ7xv3SQgmnLe5orh7zDt8ah6JybBsTPhkT1Ng");
     }
 }
@@ -9,7 +9,7 @@ pub mod core;
 pub mod info;
 pub mod wasm;
 
-#[cfg(feature = "tests")]
+#[cfg(any(test, feature = "tests"))]
 pub mod tests;
 
 #[cfg(feature = "wallet_info")]
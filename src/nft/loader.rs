@@ -0,0 +1,305 @@
+use std::{collections::HashMap, time::Duration};
+
+use async_trait::async_trait;
+use futures::{stream, StreamExt};
+use mpl_token_metadata::state::Metadata;
+use serde_json::Value;
+
+use crate::{
+    core::{
+        error::{RpcError, Web3WasmError, Web3WasmResult},
+        transport::with_timeout,
+    },
+    nft::structs::NftInfo,
+};
+
+/// Off-chain documents fetched at once by [`OffChainMetadataLoader::load_many`].
+pub const MAX_CONCURRENT_FETCHES: usize = 8;
+
+/// Fetches a JSON document over HTTP, swappable for offline tests.
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+pub trait HttpFetcher: Send + Sync {
+    async fn fetch_json(&self, url: &str) -> Web3WasmResult<Value>;
+}
+
+/// `GET` through `reqwest`, with an optional timeout.
+#[derive(Default)]
+pub struct ReqwestFetcher {
+    timeout: Option<Duration>,
+    http: reqwest::Client,
+}
+
+impl ReqwestFetcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl HttpFetcher for ReqwestFetcher {
+    async fn fetch_json(&self, url: &str) -> Web3WasmResult<Value> {
+        with_timeout(
+            async {
+                let response = self
+                    .http
+                    .get(url)
+                    .send()
+                    .await
+                    .map_err(|err| RpcError::Transport(err.to_string()))?;
+                let status = response.status();
+                if !status.is_success() {
+                    return Err(RpcError::HttpStatus {
+                        status: status.as_u16(),
+                        retry_after: None,
+                        body: response.text().await.unwrap_or_default(),
+                    }
+                    .into());
+                }
+                response
+                    .json::<Value>()
+                    .await
+                    .map_err(Web3WasmError::decode)
+            },
+            self.timeout,
+        )
+        .await
+    }
+}
+
+/// Serves canned documents by URL, no network involved.
+#[derive(Debug, Default)]
+pub struct MockFetcher {
+    documents: HashMap<String, Value>,
+}
+
+impl MockFetcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn document(mut self, url: impl Into<String>, json: Value) -> Self {
+        self.documents.insert(url.into(), json);
+        self
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+impl HttpFetcher for MockFetcher {
+    async fn fetch_json(&self, url: &str) -> Web3WasmResult<Value> {
+        self.documents.get(url).cloned().ok_or_else(|| {
+            RpcError::HttpStatus {
+                status: 404,
+                retry_after: None,
+                body: format!("No document for {url}"),
+            }
+            .into()
+        })
+    }
+}
+
+/// HTTP gateways `ipfs://` and `ar://` URIs are rewritten to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Gateways {
+    pub ipfs: String,
+    pub arweave: String,
+}
+
+impl Default for Gateways {
+    fn default() -> Self {
+        Self {
+            ipfs: "https://ipfs.io/ipfs/".to_owned(),
+            arweave: "https://arweave.net/".to_owned(),
+        }
+    }
+}
+
+impl Gateways {
+    /// Fetchable URL of a metadata `uri`, HTTP URIs are returned as is.
+    pub fn resolve(&self, uri: &str) -> Web3WasmResult<String> {
+        let uri = uri.trim_matches(char::from(0)).trim();
+        if uri.is_empty() {
+            return Err(Web3WasmError::InvalidInput("Empty metadata uri".to_owned()));
+        }
+
+        if let Some(path) = uri.strip_prefix("ipfs://") {
+            let path = path.strip_prefix("ipfs/").unwrap_or(path);
+            return Ok(join(&self.ipfs, path));
+        }
+        if let Some(path) = uri.strip_prefix("ar://") {
+            return Ok(join(&self.arweave, path));
+        }
+        Ok(uri.to_owned())
+    }
+}
+
+fn join(gateway: &str, path: &str) -> String {
+    format!(
+        "{}/{}",
+        gateway.trim_end_matches('/'),
+        path.trim_start_matches('/')
+    )
+}
+
+/// Loads the off-chain JSON that on-chain `Metadata` points to.
+pub struct OffChainMetadataLoader<F = ReqwestFetcher> {
+    fetcher: F,
+    gateways: Gateways,
+    max_concurrent: usize,
+}
+
+impl Default for OffChainMetadataLoader {
+    fn default() -> Self {
+        Self::with_fetcher(ReqwestFetcher::new())
+    }
+}
+
+impl<F: HttpFetcher> OffChainMetadataLoader<F> {
+    pub fn with_fetcher(fetcher: F) -> Self {
+        Self {
+            fetcher,
+            gateways: Gateways::default(),
+            max_concurrent: MAX_CONCURRENT_FETCHES,
+        }
+    }
+
+    pub fn gateways(mut self, gateways: Gateways) -> Self {
+        self.gateways = gateways;
+        self
+    }
+
+    pub fn max_concurrent(mut self, max_concurrent: usize) -> Self {
+        self.max_concurrent = max_concurrent.max(1);
+        self
+    }
+
+    pub async fn load(&self, uri: &str) -> Web3WasmResult<NftInfo> {
+        let url = self.gateways.resolve(uri)?;
+        let json = self.fetcher.fetch_json(&url).await?;
+        NftInfo::from_json(&json)
+    }
+
+    pub async fn load_metadata(&self, metadata: &Metadata) -> Web3WasmResult<NftInfo> {
+        self.load(&metadata.data.uri).await
+    }
+
+    /// Loads every uri, at most `max_concurrent` at a time, in input order.
+    ///
+    /// A failed download only fails its own entry.
+    pub async fn load_many<S: AsRef<str>>(&self, uris: &[S]) -> Vec<Web3WasmResult<NftInfo>> {
+        stream::iter(uris)
+            .map(|uri| self.load(uri.as_ref()))
+            .buffered(self.max_concurrent)
+            .collect()
+            .await
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(target_arch = "wasm32"))]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+    use crate::nft::structs::{Attribute, Creator, File};
+
+    #[test]
+    fn test_resolve_gateways() {
+        let gateways = Gateways::default();
+        assert_eq!(
+            gateways.resolve("ipfs://bafy123/0.json").unwrap(),
+            "https://ipfs.io/ipfs/bafy123/0.json"
+        );
+        assert_eq!(
+            gateways.resolve("ipfs://ipfs/bafy123").unwrap(),
+            "https://ipfs.io/ipfs/bafy123"
+        );
+        assert_eq!(
+            gateways.resolve("ar://tx123\0\0\0").unwrap(),
+            "https://arweave.net/tx123"
+        );
+        assert_eq!(
+            gateways.resolve("https://example.com/1.json").unwrap(),
+            "https://example.com/1.json"
+        );
+        assert_eq!(
+            gateways.resolve("\0\0").unwrap_err().code(),
+            "INVALID_INPUT"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_load_many() {
+        let fetcher = MockFetcher::new()
+            .document(
+                "https://arweave.net/nft0",
+                json!({
+                    "name": "Ava #0",
+                    "symbol": "AVA",
+                    "seller_fee_basis_points": "500",
+                    "image": "https://arweave.net/img0",
+                    "attributes": [
+                        { "trait_type": "Level", "value": 3, "display_type": "number" },
+                        "not an attribute"
+                    ],
+                    "properties": {
+                        "category": "image",
+                        "creators": [{ "address": "9K9RDUPvRfcVmHnoThUGkdR2bfQwa9oH1bs8RsmR2fjc", "share": 100 }],
+                        "files": [{ "uri": "https://arweave.net/img0", "type": "image/png" }]
+                    }
+                }),
+            )
+            .document("https://ipfs.io/ipfs/nft1", json!({ "name": "Bare" }))
+            .document("https://ipfs.io/ipfs/broken", json!("not an object"));
+        let loader = OffChainMetadataLoader::with_fetcher(fetcher).max_concurrent(2);
+
+        let results = loader
+            .load_many(&[
+                "ar://nft0",
+                "ipfs://nft1",
+                "ipfs://missing",
+                "ipfs://broken",
+            ])
+            .await;
+
+        let nft = results[0].as_ref().unwrap();
+        assert_eq!(nft.name, "Ava #0");
+        assert_eq!(nft.seller_fee_basis_points, 500);
+        assert_eq!(
+            nft.attributes,
+            vec![Attribute {
+                trait_type: "Level".to_owned(),
+                value: "3".to_owned(),
+                display_type: Some("number".to_owned()),
+            }]
+        );
+        assert_eq!(
+            nft.properties.creators,
+            vec![Creator {
+                address: "9K9RDUPvRfcVmHnoThUGkdR2bfQwa9oH1bs8RsmR2fjc".to_owned(),
+                share: 100,
+            }]
+        );
+        assert_eq!(
+            nft.properties.files,
+            vec![File {
+                uri: "https://arweave.net/img0".to_owned(),
+                r#type: "image/png".to_owned(),
+            }]
+        );
+
+        let bare = results[1].as_ref().unwrap();
+        assert_eq!(bare.name, "Bare");
+        assert!(bare.attributes.is_empty());
+
+        assert_eq!(results[2].as_ref().unwrap_err().code(), "RPC_ERROR");
+        assert_eq!(results[3].as_ref().unwrap_err().code(), "DECODE_ERROR");
+    }
+}
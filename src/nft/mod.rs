@@ -1,2 +1,4 @@
 #[cfg(feature = "nft_info")]
+pub mod loader;
+#[cfg(feature = "nft_info")]
 pub mod structs;
@@ -1,35 +1,141 @@
-#![allow(dead_code)]
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::core::error::{Web3WasmError, Web3WasmResult};
+
+/// Off-chain JSON metadata of an NFT, following the Metaplex token standard.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct NftInfo {
-    name: String,
-    symbol: String,
-    description: String,
-    seller_fee_basis_points: u32,
-    external_url: String,
-    edition: String,
-    background_color: String,
-    attributes: Vec<Attribute>,
-    properties: TokenProperties,
-    image: String,
+    pub name: String,
+    pub symbol: String,
+    pub description: String,
+    pub seller_fee_basis_points: u32,
+    pub external_url: String,
+    pub edition: String,
+    pub background_color: String,
+    pub attributes: Vec<Attribute>,
+    pub properties: TokenProperties,
+    pub image: String,
+    pub animation_url: Option<String>,
 }
 
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct Attribute {
-    trait_type: String,
-    value: String,
-    display_type: Option<String>,
+    pub trait_type: String,
+    pub value: String,
+    pub display_type: Option<String>,
 }
 
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct TokenProperties {
-    category: String,
-    creators: Vec<Creator>,
-    files: Vec<File>,
+    pub category: String,
+    pub creators: Vec<Creator>,
+    pub files: Vec<File>,
 }
 
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct Creator {
-    address: String,
-    share: u8,
+    pub address: String,
+    pub share: u8,
 }
 
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct File {
-    uri: String,
-    r#type: String,
+    pub uri: String,
+    pub r#type: String,
+}
+
+// Parse -------------------------------------
+
+/// Strings, numbers and booleans as text, since collections mix them freely.
+fn text(value: Option<&Value>) -> Option<String> {
+    match value? {
+        Value::String(value) => Some(value.clone()),
+        Value::Number(value) => Some(value.to_string()),
+        Value::Bool(value) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+fn string_field(object: &Map<String, Value>, key: &str) -> String {
+    text(object.get(key)).unwrap_or_default()
+}
+
+fn number_field(object: &Map<String, Value>, key: &str) -> Option<u64> {
+    match object.get(key)? {
+        Value::Number(value) => value.as_u64(),
+        Value::String(value) => value.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+/// Entries of `key` that are objects, anything else is skipped.
+fn objects<'a>(
+    object: &'a Map<String, Value>,
+    key: &str,
+) -> impl Iterator<Item = &'a Map<String, Value>> {
+    object
+        .get(key)
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_object)
+}
+
+impl NftInfo {
+    /// Parses the JSON a metadata `uri` points to.
+    ///
+    /// Only a non-object document is an error: missing or mistyped fields fall
+    /// back to their default, and numbers given as strings are accepted.
+    pub fn from_json(json: &Value) -> Web3WasmResult<Self> {
+        let object = json.as_object().ok_or_else(|| {
+            Web3WasmError::Decode("NFT metadata JSON is not an object".to_owned())
+        })?;
+
+        let attributes = objects(object, "attributes")
+            .map(|attribute| Attribute {
+                trait_type: string_field(attribute, "trait_type"),
+                value: string_field(attribute, "value"),
+                display_type: text(attribute.get("display_type")),
+            })
+            .collect();
+
+        let properties = object
+            .get("properties")
+            .and_then(Value::as_object)
+            .map(|properties| TokenProperties {
+                category: string_field(properties, "category"),
+                creators: objects(properties, "creators")
+                    .map(|creator| Creator {
+                        address: string_field(creator, "address"),
+                        share: number_field(creator, "share")
+                            .and_then(|share| u8::try_from(share).ok())
+                            .unwrap_or_default(),
+                    })
+                    .collect(),
+                files: objects(properties, "files")
+                    .map(|file| File {
+                        uri: string_field(file, "uri"),
+                        r#type: string_field(file, "type"),
+                    })
+                    .collect(),
+            })
+            .unwrap_or_default();
+
+        Ok(Self {
+            name: string_field(object, "name"),
+            symbol: string_field(object, "symbol"),
+            description: string_field(object, "description"),
+            seller_fee_basis_points: number_field(object, "seller_fee_basis_points")
+                .and_then(|fee| u32::try_from(fee).ok())
+                .unwrap_or_default(),
+            external_url: string_field(object, "external_url"),
+            edition: string_field(object, "edition"),
+            background_color: string_field(object, "background_color"),
+            attributes,
+            properties,
+            image: string_field(object, "image"),
+            animation_url: text(object.get("animation_url")).filter(|url| !url.is_empty()),
+        })
+    }
 }
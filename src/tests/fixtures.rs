@@ -0,0 +1,15 @@
+#![cfg(test)]
+#![cfg(not(target_arch = "wasm32"))]
+
+use solana_sdk::commitment_config::CommitmentLevel;
+
+use crate::core::{json_rpc::JsonRpcClient, transport::MockTransport};
+
+/// Offline client replaying `tests/fixtures/<name>.json`.
+pub fn fixture_client(name: &str) -> JsonRpcClient<MockTransport> {
+    let path = format!("{}/tests/fixtures/{name}.json", env!("CARGO_MANIFEST_DIR"));
+    let transport = MockTransport::from_file(&path)
+        .unwrap_or_else(|err| panic!("Failed to load fixture {path}: {err}"));
+
+    JsonRpcClient::with_transport(transport, CommitmentLevel::Confirmed)
+}
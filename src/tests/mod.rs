@@ -1,2 +1,6 @@
 pub mod balance;
+pub mod fixtures;
 pub mod mock;
+pub mod rpc_server;
+#[cfg(feature = "pubsub")]
+pub mod ws_server;
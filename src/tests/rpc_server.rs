@@ -0,0 +1,256 @@
+#![cfg(test)]
+#![cfg(not(target_arch = "wasm32"))]
+#![allow(dead_code)]
+
+use std::sync::{Arc, Mutex};
+
+use serde_json::{json, Value};
+use solana_sdk::pubkey::Pubkey;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+
+/// Canned HTTP response served by [`StandInRpcServer`].
+#[derive(Debug, Clone)]
+pub struct StandInResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Value,
+}
+
+impl StandInResponse {
+    /// `200 OK` with a JSON-RPC `result`.
+    pub fn result(result: Value) -> Self {
+        Self {
+            status: 200,
+            headers: vec![],
+            body: json!({ "jsonrpc": "2.0", "result": result, "id": 1 }),
+        }
+    }
+
+    /// `200 OK` with a JSON-RPC `error`.
+    pub fn rpc_error(code: i64, message: &str) -> Self {
+        Self {
+            status: 200,
+            headers: vec![],
+            body: json!({
+                "jsonrpc": "2.0",
+                "error": { "code": code, "message": message },
+                "id": 1
+            }),
+        }
+    }
+
+    /// Non-2xx HTTP status, with a JSON-RPC error body like public RPCs send.
+    pub fn status(status: u16) -> Self {
+        Self {
+            status,
+            headers: vec![],
+            body: json!({
+                "jsonrpc": "2.0",
+                "error": { "code": status, "message": reason_phrase(status) },
+                "id": 1
+            }),
+        }
+    }
+
+    /// `200 OK` with a `{ context, value }` result, like the account methods return.
+    pub fn value(value: Value) -> Self {
+        Self::result(json!({ "context": { "slot": 1 }, "value": value }))
+    }
+
+    /// `getMultipleAccounts` result with `account(key)` for every requested key, `null`
+    /// where it returns `None`.
+    pub fn accounts(params: &Value, account: impl Fn(&Pubkey) -> Option<Value>) -> Self {
+        let value = params[0]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|key| {
+                let key = key.as_str().unwrap().parse::<Pubkey>().unwrap();
+                account(&key).unwrap_or(Value::Null)
+            })
+            .collect::<Vec<_>>();
+        Self::value(json!(value))
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_owned(), value.to_owned()));
+        self
+    }
+}
+
+/// Base64 encoded account of `owner` holding `data`.
+pub fn account_json(owner: &Pubkey, data: &[u8]) -> Value {
+    json!({
+        "data": [base64::encode(data), "base64"],
+        "executable": false,
+        "lamports": 1,
+        "owner": owner.to_string(),
+        "rentEpoch": 0
+    })
+}
+
+type Handler = dyn Fn(&str, &Value) -> StandInResponse + Send + Sync;
+
+/// Local HTTP server that answers JSON-RPC requests with canned responses.
+///
+/// The handler receives the JSON-RPC `method` and `params` of each request.
+pub struct StandInRpcServer {
+    url: String,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub headers: Vec<(String, String)>,
+    pub body: Value,
+}
+
+impl RecordedRequest {
+    pub fn method(&self) -> &str {
+        self.body["method"].as_str().unwrap_or_default()
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+impl StandInRpcServer {
+    pub async fn start<F>(handler: F) -> Self
+    where
+        F: Fn(&str, &Value) -> StandInResponse + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(vec![]));
+        let handler: Arc<Handler> = Arc::new(handler);
+
+        let recorded = requests.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let handler = handler.clone();
+                let recorded = recorded.clone();
+                tokio::spawn(async move {
+                    serve(stream, handler, recorded).await;
+                });
+            }
+        });
+
+        Self { url, requests }
+    }
+
+    /// URL of a port nothing listens on, for connection failures.
+    pub fn unreachable_url() -> String {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+        url
+    }
+
+    pub fn url(&self) -> String {
+        self.url.clone()
+    }
+
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    pub fn request_count(&self, method: &str) -> usize {
+        self.requests()
+            .iter()
+            .filter(|request| request.method() == method)
+            .count()
+    }
+}
+
+async fn serve(
+    mut stream: TcpStream,
+    handler: Arc<Handler>,
+    recorded: Arc<Mutex<Vec<RecordedRequest>>>,
+) {
+    let mut buffer = vec![];
+    let mut chunk = [0u8; 4096];
+
+    // 1. Read headers
+    let header_end = loop {
+        let read = match stream.read(&mut chunk).await {
+            Ok(0) | Err(_) => return,
+            Ok(read) => read,
+        };
+        buffer.extend_from_slice(&chunk[..read]);
+        if let Some(index) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+            break index + 4;
+        }
+    };
+
+    let head = String::from_utf8_lossy(&buffer[..header_end]).to_string();
+    let headers = head
+        .lines()
+        .skip(1)
+        .filter_map(|line| line.split_once(':'))
+        .map(|(key, value)| (key.trim().to_owned(), value.trim().to_owned()))
+        .collect::<Vec<_>>();
+    let content_length = headers
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, value)| value.parse::<usize>().ok())
+        .unwrap_or_default();
+
+    // 2. Read body
+    while buffer.len() < header_end + content_length {
+        let read = match stream.read(&mut chunk).await {
+            Ok(0) | Err(_) => return,
+            Ok(read) => read,
+        };
+        buffer.extend_from_slice(&chunk[..read]);
+    }
+
+    let body: Value = serde_json::from_slice(&buffer[header_end..header_end + content_length])
+        .unwrap_or_default();
+    recorded.lock().unwrap().push(RecordedRequest {
+        headers,
+        body: body.clone(),
+    });
+
+    // 3. Respond, echoing the request id
+    let method = body["method"].as_str().unwrap_or_default();
+    let mut response = handler(method, &body["params"]);
+    if response.body.get("id").is_some() {
+        response.body["id"] = body["id"].clone();
+    }
+
+    let payload = response.body.to_string();
+    let mut head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n",
+        response.status,
+        reason_phrase(response.status),
+        payload.len()
+    );
+    for (name, value) in &response.headers {
+        head.push_str(&format!("{name}: {value}\r\n"));
+    }
+    head.push_str("\r\n");
+
+    stream.write_all(head.as_bytes()).await.ok();
+    stream.write_all(payload.as_bytes()).await.ok();
+    stream.shutdown().await.ok();
+}
+
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        404 => "Not Found",
+        429 => "Too Many Requests",
+        500 => "Internal Server Error",
+        502 => "Bad Gateway",
+        503 => "Service Unavailable",
+        504 => "Gateway Timeout",
+        _ => "Unknown",
+    }
+}
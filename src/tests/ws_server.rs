@@ -0,0 +1,120 @@
+#![cfg(test)]
+#![cfg(not(target_arch = "wasm32"))]
+#![allow(dead_code)]
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc, Mutex,
+};
+
+use futures::{SinkExt, StreamExt};
+use serde_json::Value;
+use tokio::net::TcpListener;
+use tokio_tungstenite::tungstenite::{
+    handshake::server::{ErrorResponse, Request, Response},
+    Message,
+};
+
+/// What [`StandInWsServer`] does in reply to a request.
+#[derive(Debug, Clone)]
+pub enum WsReply {
+    Message(Value),
+    /// Closes the connection.
+    Close,
+}
+
+type Handler = dyn Fn(usize, &Value) -> Vec<WsReply> + Send + Sync;
+
+/// Local websocket server answering JSON-RPC requests with canned replies.
+///
+/// The handler receives the 0-based index of the connection and the request.
+pub struct StandInWsServer {
+    url: String,
+    requests: Arc<Mutex<Vec<Value>>>,
+    handshake_headers: Arc<Mutex<Vec<Vec<(String, String)>>>>,
+}
+
+impl StandInWsServer {
+    pub async fn start<F>(handler: F) -> Self
+    where
+        F: Fn(usize, &Value) -> Vec<WsReply> + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(vec![]));
+        let handshake_headers = Arc::new(Mutex::new(vec![]));
+        let handler: Arc<Handler> = Arc::new(handler);
+        let connections = Arc::new(AtomicUsize::new(0));
+
+        let recorded = requests.clone();
+        let recorded_headers = handshake_headers.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let connection = connections.fetch_add(1, Ordering::SeqCst);
+                let handler = handler.clone();
+                let recorded = recorded.clone();
+                let recorded_headers = recorded_headers.clone();
+                tokio::spawn(async move {
+                    let record_headers = |request: &Request, response: Response| {
+                        let headers = request
+                            .headers()
+                            .iter()
+                            .map(|(name, value)| {
+                                (
+                                    name.to_string(),
+                                    value.to_str().unwrap_or_default().to_owned(),
+                                )
+                            })
+                            .collect();
+                        recorded_headers.lock().unwrap().push(headers);
+                        Ok::<_, ErrorResponse>(response)
+                    };
+                    let mut socket =
+                        match tokio_tungstenite::accept_hdr_async(stream, record_headers).await {
+                            Ok(socket) => socket,
+                            Err(_) => return,
+                        };
+
+                    while let Some(Ok(message)) = socket.next().await {
+                        let request = match message {
+                            Message::Text(text) => serde_json::from_str::<Value>(&text).unwrap(),
+                            _ => continue,
+                        };
+                        recorded.lock().unwrap().push(request.clone());
+
+                        for reply in handler(connection, &request) {
+                            match reply {
+                                WsReply::Message(message) => {
+                                    socket.send(Message::Text(message.to_string())).await.ok();
+                                }
+                                WsReply::Close => {
+                                    socket.close(None).await.ok();
+                                    return;
+                                }
+                            }
+                        }
+                    }
+                });
+            }
+        });
+
+        Self {
+            url,
+            requests,
+            handshake_headers,
+        }
+    }
+
+    pub fn url(&self) -> String {
+        self.url.clone()
+    }
+
+    pub fn requests(&self) -> Vec<Value> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// Headers of each connection's handshake request, lowercase names.
+    pub fn handshake_headers(&self) -> Vec<Vec<(String, String)>> {
+        self.handshake_headers.lock().unwrap().clone()
+    }
+}
@@ -0,0 +1,508 @@
+use mpl_token_metadata::{
+    instruction::{
+        create_metadata_accounts_v3, remove_creator_verification, set_and_verify_collection,
+        sign_metadata, update_metadata_accounts_v2, verify_collection,
+    },
+    pda::find_metadata_account,
+    state::{
+        Collection, Creator, DataV2, MAX_CREATOR_LIMIT, MAX_NAME_LENGTH, MAX_SYMBOL_LENGTH,
+        MAX_URI_LENGTH,
+    },
+};
+use solana_sdk::{instruction::Instruction, message::Message, pubkey::Pubkey};
+
+use crate::core::{
+    error::{Web3WasmError, Web3WasmResult},
+    metaplex::find_edition_account,
+    royalty::MAX_BASIS_POINTS,
+    rpc::RpcClient,
+    token_metadata::MetadataCreator,
+};
+
+/// Data of a metadata account to create or overwrite.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MetadataArgs {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub seller_fee_basis_points: u16,
+    /// Only creators signing the transaction may be `verified`.
+    pub creators: Vec<MetadataCreator>,
+    /// Collection mint, unverified until a `verify_collection`.
+    pub collection: Option<Pubkey>,
+}
+
+impl MetadataArgs {
+    /// The limits the program enforces, checked before anything is signed.
+    pub fn validate(&self) -> Web3WasmResult<()> {
+        for (field, value, max) in [
+            ("name", &self.name, MAX_NAME_LENGTH),
+            ("symbol", &self.symbol, MAX_SYMBOL_LENGTH),
+            ("uri", &self.uri, MAX_URI_LENGTH),
+        ] {
+            if value.len() > max {
+                return Err(Web3WasmError::InvalidInput(format!(
+                    "Metadata {field} is {} bytes, max is {max}",
+                    value.len()
+                )));
+            }
+        }
+
+        if self.seller_fee_basis_points > MAX_BASIS_POINTS {
+            return Err(Web3WasmError::InvalidInput(format!(
+                "Royalty of {} basis points is above {MAX_BASIS_POINTS}",
+                self.seller_fee_basis_points
+            )));
+        }
+
+        if self.creators.len() > MAX_CREATOR_LIMIT {
+            return Err(Web3WasmError::InvalidInput(format!(
+                "{} creators, max is {MAX_CREATOR_LIMIT}",
+                self.creators.len()
+            )));
+        }
+        let total: u32 = self
+            .creators
+            .iter()
+            .map(|creator| creator.share as u32)
+            .sum();
+        if !self.creators.is_empty() && total != 100 {
+            return Err(Web3WasmError::InvalidInput(format!(
+                "Creator shares add up to {total}, expected 100"
+            )));
+        }
+        Ok(())
+    }
+
+    fn to_data_v2(&self) -> DataV2 {
+        DataV2 {
+            name: self.name.clone(),
+            symbol: self.symbol.clone(),
+            uri: self.uri.clone(),
+            seller_fee_basis_points: self.seller_fee_basis_points,
+            creators: (!self.creators.is_empty()).then(|| {
+                self.creators
+                    .iter()
+                    .map(|creator| Creator {
+                        address: creator.address,
+                        verified: creator.verified,
+                        share: creator.share,
+                    })
+                    .collect()
+            }),
+            collection: self.collection.map(|key| Collection {
+                verified: false,
+                key,
+            }),
+            uses: None,
+        }
+    }
+}
+
+/// Changes to a metadata account, `None` leaves the field as is.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MetadataUpdate {
+    pub data: Option<MetadataArgs>,
+    pub new_update_authority: Option<Pubkey>,
+    pub primary_sale_happened: Option<bool>,
+    pub is_mutable: Option<bool>,
+}
+
+fn message_data_bs58(instructions: &[Instruction], payer: &Pubkey) -> String {
+    let message = Message::new(instructions, Some(payer));
+    bs58::encode(message.serialize()).into_string()
+}
+
+/// Token Metadata program instructions for a `mint`, PDAs derived for you.
+pub trait MetadataTransaction {
+    fn build_create_metadata_instruction(
+        &self,
+        mint: &Pubkey,
+        mint_authority: &Pubkey,
+        payer: &Pubkey,
+        update_authority: &Pubkey,
+        args: &MetadataArgs,
+        is_mutable: bool,
+    ) -> Web3WasmResult<Instruction>;
+
+    fn build_update_metadata_instruction(
+        &self,
+        mint: &Pubkey,
+        update_authority: &Pubkey,
+        update: &MetadataUpdate,
+    ) -> Web3WasmResult<Instruction>;
+
+    /// Marks `creator`, who signs, as verified.
+    fn build_sign_metadata_instruction(&self, mint: &Pubkey, creator: &Pubkey) -> Instruction;
+
+    fn build_remove_creator_verification_instruction(
+        &self,
+        mint: &Pubkey,
+        creator: &Pubkey,
+    ) -> Instruction;
+
+    /// Verifies the collection already set on the metadata of `mint`.
+    fn build_verify_collection_instruction(
+        &self,
+        mint: &Pubkey,
+        collection_mint: &Pubkey,
+        collection_authority: &Pubkey,
+        payer: &Pubkey,
+    ) -> Instruction;
+
+    /// Sets the collection of `mint` and verifies it, signed by both authorities.
+    fn build_set_and_verify_collection_instruction(
+        &self,
+        mint: &Pubkey,
+        update_authority: &Pubkey,
+        collection_mint: &Pubkey,
+        collection_authority: &Pubkey,
+        payer: &Pubkey,
+    ) -> Instruction;
+
+    fn build_create_metadata_message_data_bs58(
+        &self,
+        mint: &Pubkey,
+        mint_authority: &Pubkey,
+        payer: &Pubkey,
+        update_authority: &Pubkey,
+        args: &MetadataArgs,
+        is_mutable: bool,
+    ) -> Web3WasmResult<String> {
+        let ix = self.build_create_metadata_instruction(
+            mint,
+            mint_authority,
+            payer,
+            update_authority,
+            args,
+            is_mutable,
+        )?;
+        Ok(message_data_bs58(&[ix], payer))
+    }
+
+    fn build_update_metadata_message_data_bs58(
+        &self,
+        mint: &Pubkey,
+        update_authority: &Pubkey,
+        update: &MetadataUpdate,
+    ) -> Web3WasmResult<String> {
+        let ix = self.build_update_metadata_instruction(mint, update_authority, update)?;
+        Ok(message_data_bs58(&[ix], update_authority))
+    }
+
+    fn build_sign_metadata_message_data_bs58(&self, mint: &Pubkey, creator: &Pubkey) -> String {
+        message_data_bs58(
+            &[self.build_sign_metadata_instruction(mint, creator)],
+            creator,
+        )
+    }
+
+    fn build_remove_creator_verification_message_data_bs58(
+        &self,
+        mint: &Pubkey,
+        creator: &Pubkey,
+    ) -> String {
+        message_data_bs58(
+            &[self.build_remove_creator_verification_instruction(mint, creator)],
+            creator,
+        )
+    }
+
+    fn build_verify_collection_message_data_bs58(
+        &self,
+        mint: &Pubkey,
+        collection_mint: &Pubkey,
+        collection_authority: &Pubkey,
+        payer: &Pubkey,
+    ) -> String {
+        let ix = self.build_verify_collection_instruction(
+            mint,
+            collection_mint,
+            collection_authority,
+            payer,
+        );
+        message_data_bs58(&[ix], payer)
+    }
+
+    fn build_set_and_verify_collection_message_data_bs58(
+        &self,
+        mint: &Pubkey,
+        update_authority: &Pubkey,
+        collection_mint: &Pubkey,
+        collection_authority: &Pubkey,
+        payer: &Pubkey,
+    ) -> String {
+        let ix = self.build_set_and_verify_collection_instruction(
+            mint,
+            update_authority,
+            collection_mint,
+            collection_authority,
+            payer,
+        );
+        message_data_bs58(&[ix], payer)
+    }
+}
+
+impl<C: RpcClient> MetadataTransaction for C {
+    fn build_create_metadata_instruction(
+        &self,
+        mint: &Pubkey,
+        mint_authority: &Pubkey,
+        payer: &Pubkey,
+        update_authority: &Pubkey,
+        args: &MetadataArgs,
+        is_mutable: bool,
+    ) -> Web3WasmResult<Instruction> {
+        args.validate()?;
+        let data = args.to_data_v2();
+        let (metadata, _) = find_metadata_account(mint);
+
+        Ok(create_metadata_accounts_v3(
+            mpl_token_metadata::id(),
+            metadata,
+            *mint,
+            *mint_authority,
+            *payer,
+            *update_authority,
+            data.name,
+            data.symbol,
+            data.uri,
+            data.creators,
+            data.seller_fee_basis_points,
+            true,
+            is_mutable,
+            data.collection,
+            data.uses,
+            None,
+        ))
+    }
+
+    fn build_update_metadata_instruction(
+        &self,
+        mint: &Pubkey,
+        update_authority: &Pubkey,
+        update: &MetadataUpdate,
+    ) -> Web3WasmResult<Instruction> {
+        if let Some(args) = &update.data {
+            args.validate()?;
+        }
+        let (metadata, _) = find_metadata_account(mint);
+
+        Ok(update_metadata_accounts_v2(
+            mpl_token_metadata::id(),
+            metadata,
+            *update_authority,
+            update.new_update_authority,
+            update.data.as_ref().map(MetadataArgs::to_data_v2),
+            update.primary_sale_happened,
+            update.is_mutable,
+        ))
+    }
+
+    fn build_sign_metadata_instruction(&self, mint: &Pubkey, creator: &Pubkey) -> Instruction {
+        let (metadata, _) = find_metadata_account(mint);
+        sign_metadata(mpl_token_metadata::id(), metadata, *creator)
+    }
+
+    fn build_remove_creator_verification_instruction(
+        &self,
+        mint: &Pubkey,
+        creator: &Pubkey,
+    ) -> Instruction {
+        let (metadata, _) = find_metadata_account(mint);
+        remove_creator_verification(mpl_token_metadata::id(), metadata, *creator)
+    }
+
+    fn build_verify_collection_instruction(
+        &self,
+        mint: &Pubkey,
+        collection_mint: &Pubkey,
+        collection_authority: &Pubkey,
+        payer: &Pubkey,
+    ) -> Instruction {
+        let (metadata, _) = find_metadata_account(mint);
+        let (collection_metadata, _) = find_metadata_account(collection_mint);
+        let (collection_master_edition, _) = find_edition_account(collection_mint);
+
+        verify_collection(
+            mpl_token_metadata::id(),
+            metadata,
+            *collection_authority,
+            *payer,
+            *collection_mint,
+            collection_metadata,
+            collection_master_edition,
+            None,
+        )
+    }
+
+    fn build_set_and_verify_collection_instruction(
+        &self,
+        mint: &Pubkey,
+        update_authority: &Pubkey,
+        collection_mint: &Pubkey,
+        collection_authority: &Pubkey,
+        payer: &Pubkey,
+    ) -> Instruction {
+        let (metadata, _) = find_metadata_account(mint);
+        let (collection_metadata, _) = find_metadata_account(collection_mint);
+        let (collection_master_edition, _) = find_edition_account(collection_mint);
+
+        set_and_verify_collection(
+            mpl_token_metadata::id(),
+            metadata,
+            *collection_authority,
+            *payer,
+            *update_authority,
+            *collection_mint,
+            collection_metadata,
+            collection_master_edition,
+            None,
+        )
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(test)]
+mod test {
+    use borsh::BorshDeserialize;
+    use mpl_token_metadata::instruction::MetadataInstruction;
+    use solana_sdk::message::Message;
+
+    use super::*;
+    use crate::tests::fixtures::fixture_client;
+
+    fn decode(ix: &Instruction) -> MetadataInstruction {
+        assert_eq!(ix.program_id, mpl_token_metadata::id());
+        MetadataInstruction::try_from_slice(&ix.data).unwrap()
+    }
+
+    fn args(creator: &Pubkey) -> MetadataArgs {
+        MetadataArgs {
+            name: "Ava #1".to_owned(),
+            symbol: "AVA".to_owned(),
+            uri: "https://arweave.net/ava1".to_owned(),
+            seller_fee_basis_points: 500,
+            creators: vec![MetadataCreator {
+                address: *creator,
+                verified: true,
+                share: 100,
+            }],
+            collection: Some(Pubkey::new_from_array([8; 32])),
+        }
+    }
+
+    #[test]
+    fn test_build_create_and_update_metadata() {
+        let client = fixture_client("transfer");
+        let mint = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+
+        let ix = client
+            .build_create_metadata_instruction(
+                &mint,
+                &authority,
+                &authority,
+                &authority,
+                &args(&authority),
+                true,
+            )
+            .unwrap();
+        assert_eq!(ix.accounts[0].pubkey, find_metadata_account(&mint).0);
+        match decode(&ix) {
+            MetadataInstruction::CreateMetadataAccountV3(create) => {
+                assert_eq!(create.data.name, "Ava #1");
+                assert_eq!(create.data.seller_fee_basis_points, 500);
+                assert_eq!(create.data.creators.unwrap()[0].address, authority);
+                assert!(!create.data.collection.unwrap().verified);
+                assert!(create.is_mutable);
+            }
+            _ => panic!("expected CreateMetadataAccountV3"),
+        }
+
+        let new_authority = Pubkey::new_unique();
+        let ix = client
+            .build_update_metadata_instruction(
+                &mint,
+                &authority,
+                &MetadataUpdate {
+                    new_update_authority: Some(new_authority),
+                    primary_sale_happened: Some(true),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        match decode(&ix) {
+            MetadataInstruction::UpdateMetadataAccountV2(update) => {
+                assert!(update.data.is_none());
+                assert_eq!(update.update_authority, Some(new_authority));
+                assert_eq!(update.primary_sale_happened, Some(true));
+                assert_eq!(update.is_mutable, None);
+            }
+            _ => panic!("expected UpdateMetadataAccountV2"),
+        }
+
+        let mut too_long = args(&authority);
+        too_long.symbol = "TOOLONGSYMBOL".to_owned();
+        let err = client
+            .build_create_metadata_message_data_bs58(
+                &mint, &authority, &authority, &authority, &too_long, true,
+            )
+            .unwrap_err();
+        assert_eq!(err.code(), "INVALID_INPUT");
+
+        let mut bad_shares = args(&authority);
+        bad_shares.creators[0].share = 90;
+        assert!(bad_shares.validate().is_err());
+    }
+
+    #[test]
+    fn test_build_creator_and_collection_instructions() {
+        let client = fixture_client("transfer");
+        let mint = Pubkey::new_unique();
+        let collection_mint = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+
+        let ix = client.build_sign_metadata_instruction(&mint, &authority);
+        assert!(matches!(decode(&ix), MetadataInstruction::SignMetadata));
+        assert!(ix.accounts[1].is_signer);
+
+        let ix = client.build_remove_creator_verification_instruction(&mint, &authority);
+        assert!(matches!(
+            decode(&ix),
+            MetadataInstruction::RemoveCreatorVerification
+        ));
+
+        let ix = client.build_verify_collection_instruction(
+            &mint,
+            &collection_mint,
+            &authority,
+            &authority,
+        );
+        assert!(matches!(decode(&ix), MetadataInstruction::VerifyCollection));
+        let keys = ix
+            .accounts
+            .iter()
+            .map(|meta| meta.pubkey)
+            .collect::<Vec<_>>();
+        assert!(keys.contains(&find_metadata_account(&collection_mint).0));
+        assert!(keys.contains(&find_edition_account(&collection_mint).0));
+
+        let message_b58 = client.build_set_and_verify_collection_message_data_bs58(
+            &mint,
+            &authority,
+            &collection_mint,
+            &authority,
+            &authority,
+        );
+        let message: Message =
+            bincode::deserialize(&bs58::decode(message_b58).into_vec().unwrap()).unwrap();
+        assert_eq!(message.account_keys[0], authority);
+        assert_eq!(message.instructions.len(), 1);
+        let data = &message.instructions[0].data;
+        assert!(matches!(
+            MetadataInstruction::try_from_slice(data).unwrap(),
+            MetadataInstruction::SetAndVerifyCollection
+        ));
+    }
+}
@@ -1,2 +1,3 @@
+pub mod metadata;
 pub mod token22_transfer;
 pub mod token_transfer;
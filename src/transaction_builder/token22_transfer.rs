@@ -1,6 +1,3 @@
-use anyhow::bail;
-
-use solana_client_wasm::WasmClient;
 use solana_sdk::{instruction::Instruction, message::Message, pubkey::Pubkey, system_instruction};
 
 use spl_associated_token_account::{
@@ -12,6 +9,8 @@ use async_trait::async_trait;
 use solana_client_wasm::utils::rpc_filter::TokenAccountsFilter;
 use solana_extra_wasm::program::{spl_token_2022, spl_token_2022::instruction::transfer_checked};
 
+use crate::core::{error::Web3WasmResult, rpc::RpcClient};
+
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
 pub trait Token22Transfer {
@@ -20,14 +19,14 @@ pub trait Token22Transfer {
         source: &Pubkey,
         destination: &Pubkey,
         amount: u64,
-    ) -> anyhow::Result<Vec<Instruction>>;
+    ) -> Web3WasmResult<Vec<Instruction>>;
 
     fn build_transfer_native_instruction_message_data_bs58(
         &self,
         source: &Pubkey,
         destination: &Pubkey,
         amount: u64,
-    ) -> anyhow::Result<String>;
+    ) -> Web3WasmResult<String>;
 
     async fn build_transfer_spl_instructions(
         &self,
@@ -36,7 +35,7 @@ pub trait Token22Transfer {
         mint_pubkey: &Pubkey,
         amount: u64,
         decimals: u8,
-    ) -> anyhow::Result<Vec<Instruction>>;
+    ) -> Web3WasmResult<Vec<Instruction>>;
 
     async fn build_transfer_spl_instructions_message_data_bs58(
         &self,
@@ -45,18 +44,18 @@ pub trait Token22Transfer {
         mint_pubkey: &Pubkey,
         amount: u64,
         decimals: u8,
-    ) -> anyhow::Result<String>;
+    ) -> Web3WasmResult<String>;
 }
 
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
-impl Token22Transfer for WasmClient {
+impl<C: RpcClient> Token22Transfer for C {
     fn build_transfer_native_instruction(
         &self,
         source: &Pubkey,
         destination: &Pubkey,
         amount: u64,
-    ) -> anyhow::Result<Vec<Instruction>> {
+    ) -> Web3WasmResult<Vec<Instruction>> {
         // 0. Init
         let mut instructions: Vec<Instruction> = vec![];
 
@@ -73,7 +72,7 @@ impl Token22Transfer for WasmClient {
         source: &Pubkey,
         destination: &Pubkey,
         amount: u64,
-    ) -> anyhow::Result<String> {
+    ) -> Web3WasmResult<String> {
         // 1. Build transfer ix
         match self.build_transfer_native_instruction(source, destination, amount) {
             Ok(instructions) => {
@@ -83,7 +82,7 @@ impl Token22Transfer for WasmClient {
 
                 Ok(message_b58)
             }
-            Err(e) => bail!(e),
+            Err(e) => Err(e),
         }
     }
 
@@ -94,7 +93,7 @@ impl Token22Transfer for WasmClient {
         mint_pubkey: &Pubkey,
         amount: u64,
         decimals: u8,
-    ) -> anyhow::Result<Vec<Instruction>> {
+    ) -> Web3WasmResult<Vec<Instruction>> {
         // 0. Init
         let mut instructions: Vec<Instruction> = vec![];
         let spl_token_id = spl_token_2022::id();
@@ -109,7 +108,7 @@ impl Token22Transfer for WasmClient {
         let token_account_filter = TokenAccountsFilter::Mint(*mint_pubkey);
 
         let accounts = match self
-            .get_token_accounts_by_owner(destination, token_account_filter)
+            .get_token_accounts_by_owner(destination, &token_account_filter)
             .await
         {
             Ok(accounts) => accounts,
@@ -143,7 +142,7 @@ impl Token22Transfer for WasmClient {
             amount,
             decimals,
         ) {
-            Err(err) => bail!(err),
+            Err(err) => return Err(err.into()),
             Ok(data) => data,
         };
 
@@ -159,7 +158,7 @@ impl Token22Transfer for WasmClient {
         mint_pubkey: &Pubkey,
         amount: u64,
         decimals: u8,
-    ) -> anyhow::Result<String> {
+    ) -> Web3WasmResult<String> {
         // Get instructions.
         let instructions = self
             .build_transfer_spl_instructions(source, destination, mint_pubkey, amount, decimals)
@@ -263,6 +262,7 @@ mod test {
 
     // Fork from https://github.com/solana-labs/solana-program-library/blob/24d54db8b8c538c0027198971002c155348e7e3d/associated-token-account/program-test/tests/extended_mint.rs
     #[tokio::test]
+    #[ignore]
     async fn test_associated_token_account_with_transfer_fees() {
         // Context
         let test_context = TestContext::new().await;
@@ -1,6 +1,3 @@
-use anyhow::bail;
-
-use solana_client_wasm::WasmClient;
 use solana_sdk::{instruction::Instruction, message::Message, pubkey::Pubkey, system_instruction};
 
 use spl_associated_token_account::{
@@ -12,6 +9,8 @@ use async_trait::async_trait;
 use solana_client_wasm::utils::rpc_filter::TokenAccountsFilter;
 use solana_extra_wasm::program::spl_token::instruction::transfer_checked;
 
+use crate::core::{error::Web3WasmResult, rpc::RpcClient};
+
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
 pub trait TokenTransfer {
@@ -20,7 +19,7 @@ pub trait TokenTransfer {
         source: &Pubkey,
         destination: &Pubkey,
         amount: u64,
-    ) -> Result<String, anyhow::Error>;
+    ) -> Web3WasmResult<String>;
 
     async fn get_message_data_bs58_for_transfer_spl(
         &self,
@@ -29,18 +28,18 @@ pub trait TokenTransfer {
         mint_pubkey: &Pubkey,
         amount: u64,
         decimals: u8,
-    ) -> Result<String, anyhow::Error>;
+    ) -> Web3WasmResult<String>;
 }
 
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
-impl TokenTransfer for WasmClient {
+impl<C: RpcClient> TokenTransfer for C {
     fn get_message_data_bs58_for_transfer_native(
         &self,
         source: &Pubkey,
         destination: &Pubkey,
         amount: u64,
-    ) -> Result<String, anyhow::Error> {
+    ) -> Web3WasmResult<String> {
         // 0. Init
         let mut instructions: Vec<Instruction> = vec![];
 
@@ -63,7 +62,7 @@ impl TokenTransfer for WasmClient {
         mint_pubkey: &Pubkey,
         amount: u64,
         decimals: u8,
-    ) -> Result<String, anyhow::Error> {
+    ) -> Web3WasmResult<String> {
         // 0. Init
         let mut instructions: Vec<Instruction> = vec![];
         let spl_token_id = spl_token::id();
@@ -78,7 +77,7 @@ impl TokenTransfer for WasmClient {
         let token_account_filter = TokenAccountsFilter::Mint(*mint_pubkey);
 
         let accounts = match self
-            .get_token_accounts_by_owner(destination, token_account_filter)
+            .get_token_accounts_by_owner(destination, &token_account_filter)
             .await
         {
             Ok(accounts) => accounts,
@@ -112,7 +111,7 @@ impl TokenTransfer for WasmClient {
             amount,
             decimals,
         ) {
-            Err(err) => bail!(err),
+            Err(err) => return Err(err.into()),
             Ok(data) => data,
         };
 
@@ -131,11 +130,11 @@ impl TokenTransfer for WasmClient {
 mod test {
     use super::TokenTransfer;
 
-    use crate::core::client::Web3WasmClient;
-    use solana_client_wasm::WasmClient;
+    use crate::core::rpc::RpcClient;
+    use crate::tests::fixtures::fixture_client;
     use solana_extra_wasm::program::spl_token::state::Mint;
 
-    use solana_sdk::{program_pack::Pack, pubkey::Pubkey};
+    use solana_sdk::{message::Message, program_pack::Pack, pubkey::Pubkey};
     use std::str::FromStr;
 
     #[tokio::test]
@@ -146,14 +145,12 @@ mod test {
             Pubkey::from_str("DZQVs9FhoWMG19nL3ofmhpQRTjbHgKzM1CitskSGM9mJ").unwrap();
         let mint_pubkey = Pubkey::from_str("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v").unwrap();
 
-        // TODO: consider move this to transfer_spl to cross-check input decimals
-        // TODO: use devnet with some mint.
-        let client = WasmClient::new_mainnet();
-        let account = client.get_account(&mint_pubkey).await.unwrap();
+        let client = fixture_client("transfer");
+        let account = RpcClient::get_account(&client, &mint_pubkey).await.unwrap();
         let mint_info = Mint::unpack(&account.data).unwrap();
         let decimals = mint_info.decimals;
-        let ui_amount = 0.00001f64;
-        let amount = spl_token::ui_amount_to_amount(ui_amount, decimals);
+        assert_eq!(decimals, 6);
+        let amount = spl_token::ui_amount_to_amount(0.00001f64, decimals);
 
         let message_b58 = client
             .get_message_data_bs58_for_transfer_spl(
@@ -166,6 +163,9 @@ mod test {
             .await
             .unwrap();
 
-        assert!(!message_b58.is_empty());
+        // Destination has no ATA yet: create ATA + transfer_checked.
+        let message: Message =
+            bincode::deserialize(&bs58::decode(message_b58).into_vec().unwrap()).unwrap();
+        assert_eq!(message.instructions.len(), 2);
     }
 }
@@ -1,11 +1,13 @@
-use anyhow::bail;
 use solana_sdk::transaction::{Transaction, VersionedTransaction};
 
 use strum_macros::EnumString;
 #[cfg(feature = "wasm_bindgen")]
 use wasm_bindgen::prelude::*;
 
-use crate::wallet::transaction_v0::TransactionV0Value;
+use crate::{
+    core::error::{Web3WasmError, Web3WasmResult},
+    wallet::transaction_v0::TransactionV0Value,
+};
 
 use super::transaction::TransactionValue;
 
@@ -20,7 +22,7 @@ pub enum EncodingType {
 
 // Fun -------------------------------------
 
-pub fn get_versioned_transaction_from_string(tx_str: &str) -> anyhow::Result<VersionedTransaction> {
+pub fn get_versioned_transaction_from_string(tx_str: &str) -> Web3WasmResult<VersionedTransaction> {
     let tx_json = serde_json::from_str(tx_str)?;
     let tx_value = serde_json::from_value::<TransactionValue>(tx_json);
 
@@ -41,7 +43,7 @@ pub fn get_versioned_transaction_from_string(tx_str: &str) -> anyhow::Result<Ver
 pub fn get_encoded_message_data_from_string(
     tx_str: &str,
     encoding_type: &EncodingType,
-) -> anyhow::Result<String> {
+) -> Web3WasmResult<String> {
     // Parse transaction
     let tx = get_versioned_transaction_from_string(tx_str)?;
     let message_data = tx.message.serialize();
@@ -58,7 +60,7 @@ pub fn get_encoded_message_data_from_string(
 pub fn get_multiple_message_data_from_string(
     txs: &[String],
     encoding_type: &EncodingType,
-) -> anyhow::Result<Vec<String>> {
+) -> Web3WasmResult<Vec<String>> {
     let mut errors = vec![];
     let result = txs
         .iter()
@@ -67,7 +69,7 @@ pub fn get_multiple_message_data_from_string(
         .collect::<Vec<_>>();
 
     if !errors.is_empty() {
-        bail!("errors: {:?}", errors)
+        return Err(Web3WasmError::InvalidInput(format!("errors: {:?}", errors)));
     }
 
     Ok(result)
@@ -78,7 +80,7 @@ pub fn get_multiple_message_data_from_string(
 pub fn get_encoded_serialized_versioned_transaction_from_string(
     tx_str: &str,
     encoding_type: &EncodingType,
-) -> anyhow::Result<String> {
+) -> Web3WasmResult<String> {
     // Parse transaction
     let tx = get_versioned_transaction_from_string(tx_str)?;
     let message_data = bincode::serialize(&tx)?;
@@ -93,7 +95,7 @@ pub fn get_encoded_serialized_versioned_transaction_from_string(
 pub fn get_multiple_encoded_serialized_versioned_transactions_from_string(
     txs: &[String],
     encoding_type: &EncodingType,
-) -> anyhow::Result<Vec<String>> {
+) -> Web3WasmResult<Vec<String>> {
     let mut errors = vec![];
     let result = txs
         .iter()
@@ -102,7 +104,7 @@ pub fn get_multiple_encoded_serialized_versioned_transactions_from_string(
         .collect::<Vec<_>>();
 
     if !errors.is_empty() {
-        bail!("errors: {:?}", errors)
+        return Err(Web3WasmError::InvalidInput(format!("errors: {:?}", errors)));
     }
 
     Ok(result)
@@ -2,6 +2,7 @@ use serde::{Deserialize, Serialize, Serializer};
 use solana_extra_wasm::account_decoder::parse_token::UiTokenAccount;
 
 use super::sort::UnsupportedAccount;
+use crate::core::token_metadata::AssetClass;
 
 #[cfg(feature = "wasm_bindgen")]
 use wasm_bindgen::prelude::*;
@@ -31,6 +32,7 @@ pub struct WalletTokenInfo {
     pub address: String, // "B8MA5aWJ7xv3SQgmnLe5orh7zDt8ah6JybBsTPhkT1Ng"
     pub mint: String, // "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v"
     pub name: String, // "USD Coin"
+    pub asset_class: AssetClass, // Fungible
 }
 
 #[cfg(feature = "wasm_bindgen")]